@@ -1,10 +1,7 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 
-use anyhow::{Result};
-use regex::Regex;
-use reqwest::blocking::Client;
 use serde::Deserialize;
-use tracing::{error, info, warn};
+use tracing::warn;
 
 use crate::config::{self, expansion_config::{Expansion, ExpansionsConfig, ItemData}, settings::{Settings, SlotSetting}};
 
@@ -116,6 +113,16 @@ pub struct ArmoryCharacterResponse {
     pub summary: ArmorySummary,
 }
 
+impl ArmoryCharacterResponse {
+    pub fn new(character: ArmoryCharacter, summary: ArmorySummary) -> Self {
+        Self {
+            _lqip: None,
+            character,
+            summary,
+        }
+    }
+}
+
 #[derive(serde::Deserialize, Clone)]
 #[allow(dead_code)]
 pub struct Achievements {
@@ -222,37 +229,6 @@ pub struct PlayerRaidData {
 }
 
 impl ArmoryChecker {
-    pub fn check_armory(name_url: &str) -> Option<ArmoryCharacterResponse> {
-        let client = Client::new();
-        let response = client
-            .get(name_url)
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36")
-            .send();
-
-        if response.is_err() {
-            error!("Error getting armory response: {:?}", response.err());
-            return None;
-        }
-
-        let text = response.unwrap().text();
-        if text.is_err() {
-            error!("Error getting armory response (text): {:?}", text.err());
-            return None;
-        }
-        let re = Regex::new(r#"var\s+characterProfileInitialState\s*=\s*(\{.*?\});"#).unwrap();
-        if let Some(captures) = re.captures(&text.unwrap()) {
-            let armory_response: Result<ArmoryCharacterResponse, serde_json::Error> = serde_json::from_str(&&captures[1]);
-            if armory_response.is_err() {
-                error!("Error parsing armory response: {:?}", armory_response.err());
-                return None;
-            }
-
-            let tmp = armory_response.unwrap();
-            return Some(tmp);
-        }
-        return None;
-    }
-
     pub fn check_raid_boss_kills(armory: &ArmoryCharacterResponse, raid_data: &mut BTreeMap<usize, PlayerRaidData>) {
         //info!("Checking raid boss kills for raid IDs: {:?}", settings.required_raids);
         //let mut unkilled_bosses = Vec::new();
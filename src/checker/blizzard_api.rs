@@ -0,0 +1,498 @@
+use std::{collections::HashMap, time::{Duration, Instant}};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use crate::{http_client, checker::{armory_checker::{AchievementCategory, AchievementSubCategory, Achievements, ArmoryCharacter, ArmoryCharacterAchievementResponse, ArmoryCharacterReputationResponse, ArmoryCharacterResponse,
+    ArmoryRaidBosses, ArmoryRaidDifficulty, ArmoryRaids, ArmorySummary, ArmoryTimestamp, CharacterGear, GearEnchantment, GearInventoryType, GearSockets, ReputationCategory, ReputationsResponse},
+    character_source::{ArmorySource, CharacterSource}, response_cache::{PageKind, ResponseCache}}, config::{expansion_config::{ExpansionRaid, ExpansionsConfig}, region::armory_character_path, settings::BlizzardApiSettings}};
+
+// Same order as the armory's raid summary, which the rest of the checkers index into.
+static DIFFICULTIES: [(&str, &str); 4] = [("LFR", "Raid Finder"), ("NORMAL", "Normal"), ("HEROIC", "Heroic"), ("MYTHIC", "Mythic")];
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize, Default)]
+struct ApiRef {
+    #[serde(default)]
+    id: i64,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ApiType {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct ApiProfile {
+    level: u8,
+    equipped_item_level: i32,
+    #[serde(default)]
+    last_login_timestamp: i64,
+}
+
+#[derive(Deserialize)]
+struct ApiEquipment {
+    #[serde(default)]
+    equipped_items: Vec<ApiEquippedItem>,
+}
+
+#[derive(Deserialize)]
+struct ApiEquippedItem {
+    item: ApiRef,
+    slot: ApiType,
+    inventory_type: GearInventoryType,
+    enchantments: Option<Vec<GearEnchantment>>,
+    sockets: Option<Vec<GearSockets>>,
+    bonus_list: Option<Vec<i32>>,
+}
+
+#[derive(Deserialize, Default)]
+struct ApiRaidEncounters {
+    #[serde(default)]
+    expansions: Vec<ApiRaidExpansion>,
+}
+
+#[derive(Deserialize)]
+struct ApiRaidExpansion {
+    instances: Vec<ApiRaidInstance>,
+}
+
+#[derive(Deserialize)]
+struct ApiRaidInstance {
+    instance: ApiRef,
+    modes: Vec<ApiRaidMode>,
+}
+
+#[derive(Deserialize)]
+struct ApiRaidMode {
+    difficulty: ApiType,
+    progress: ApiRaidProgress,
+}
+
+#[derive(Deserialize)]
+struct ApiRaidProgress {
+    encounters: Vec<ApiRaidEncounter>,
+}
+
+#[derive(Deserialize)]
+struct ApiRaidEncounter {
+    encounter: ApiRef,
+    completed_count: i32,
+    last_kill_timestamp: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ApiAchievements {
+    #[serde(default)]
+    achievements: Vec<ApiAchievement>,
+}
+
+#[derive(Deserialize)]
+struct ApiAchievement {
+    id: i32,
+    #[serde(default)]
+    achievement: ApiRef,
+    completed_timestamp: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ApiReputations {
+    #[serde(default)]
+    reputations: Vec<ApiReputation>,
+}
+
+#[derive(Deserialize)]
+struct ApiReputation {
+    faction: ApiRef,
+    standing: ApiStanding,
+}
+
+#[derive(Deserialize)]
+struct ApiStanding {
+    #[serde(default)]
+    value: u32,
+    #[serde(default)]
+    max: u32,
+    #[serde(default)]
+    name: String,
+    renown_level: Option<u32>,
+}
+
+// The armory page identifies reputations by a slug of the faction name, i.e. "Manaforge Vandals" -> "manaforge-vandals".
+fn faction_slug(name: &str) -> String {
+    name.to_lowercase()
+        .replace(['\'', '’'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+// Builds the armory style raid summary, one entry per expansion raid id, so index lookups keep working.
+fn raid_summary(raids: &[ExpansionRaid], encounters: &ApiRaidEncounters) -> ArmorySummary {
+    let instances: Vec<&ApiRaidInstance> = encounters.expansions.iter().flat_map(|x| x.instances.iter()).collect();
+    let max_id = raids.iter().map(|x| x.id).max().unwrap_or(-1);
+
+    let mut summary = ArmorySummary { raids: Vec::new() };
+    for raid_id in 0..=max_id {
+        let Some(raid) = raids.iter().find(|x| x.id == raid_id) else {
+            summary.raids.push(ArmoryRaids { difficulties: Vec::new(), name: String::new() });
+            continue;
+        };
+
+        let instance = instances.iter().find(|x| x.instance.name.eq_ignore_ascii_case(&raid.identifier));
+        let difficulties = DIFFICULTIES.iter().map(|(kind, name)| {
+            let mode = instance.and_then(|x| x.modes.iter().find(|m| m.difficulty.kind == *kind));
+            let bosses: Vec<ArmoryRaidBosses> = raid.boss_names.iter().map(|boss_name| {
+                let encounter = mode.and_then(|m| m.progress.encounters.iter().find(|e| e.encounter.name.eq_ignore_ascii_case(boss_name)));
+                ArmoryRaidBosses {
+                    kill_count: encounter.map_or(0, |e| e.completed_count),
+                    last_timestamp: encounter.and_then(|e| e.last_kill_timestamp),
+                    name: boss_name.clone(),
+                }
+            }).collect();
+
+            ArmoryRaidDifficulty {
+                name: name.to_string(),
+                count: bosses.iter().filter(|x| x.kill_count > 0).count() as i32,
+                total: bosses.len() as i32,
+                bosses,
+            }
+        }).collect();
+
+        summary.raids.push(ArmoryRaids { difficulties, name: raid.identifier.clone() });
+    }
+    summary
+}
+
+pub struct BlizzardApiSource {
+    settings: BlizzardApiSettings,
+    raids: Vec<ExpansionRaid>,
+    token: Mutex<Option<(String, Instant)>>,
+    cache: Option<ResponseCache>,
+    // The Profile API has no character search, so that still goes through the armory.
+    armory: ArmorySource,
+}
+
+impl BlizzardApiSource {
+    pub fn new(settings: BlizzardApiSettings, expansions: &ExpansionsConfig, cache: Option<ResponseCache>) -> Self {
+        let raids = expansions.latest_expansion.as_ref()
+            .map(|x| x.seasons.iter().flat_map(|s| s.raids.iter().cloned()).collect())
+            .unwrap_or_default();
+
+        Self {
+            settings,
+            raids,
+            token: Mutex::new(None),
+            cache,
+            armory: ArmorySource::new(None, None),
+        }
+    }
+
+    // Held across the request so concurrent checks share one token instead of all asking for their own.
+    async fn access_token(&self) -> Option<String> {
+        let mut token = self.token.lock().await;
+        if let Some((access_token, expires)) = token.as_ref() {
+            if Instant::now() < *expires {
+                return Some(access_token.clone());
+            }
+        }
+
+        info!("Requesting Blizzard API access token");
+        let client = http_client::client();
+        let request = client
+            .post(&self.settings.oauth_url)
+            .basic_auth(&self.settings.client_id, Some(&self.settings.client_secret))
+            .form(&[("grant_type", "client_credentials")]);
+        let response = match client.send(request).await.and_then(|x| x.error_for_status()) {
+            Ok(response) => response.json::<TokenResponse>().await,
+            Err(err) => Err(err),
+        };
+
+        match response {
+            Ok(response) => {
+                // Refresh a minute early so a token never expires mid check.
+                let expires = Instant::now() + Duration::from_secs(response.expires_in.saturating_sub(60));
+                *token = Some((response.access_token.clone(), expires));
+                Some(response.access_token)
+            },
+            Err(err) => {
+                error!("Could not get Blizzard API access token: {:?}", err);
+                None
+            }
+        }
+    }
+
+    fn api_url(&self, region: &str) -> String {
+        if self.settings.api_url.is_empty() {
+            format!("https://{}.api.blizzard.com", region)
+        } else {
+            self.settings.api_url.trim_end_matches('/').to_string()
+        }
+    }
+
+    // Turns an armory character URL into a Profile API request. Names are requested in en_US so they line up with expansions.json.
+    async fn get<T: DeserializeOwned>(&self, url: &str, endpoint: &str, kind: PageKind) -> Option<T> {
+        let path = armory_character_path(url);
+        let parts: Vec<&str> = path.split('/').collect();
+        if parts.len() != 4 {
+            error!("Not a character URL: {}", url);
+            return None;
+        }
+        let (region, realm, name) = (parts[1], parts[2], parts[3].to_lowercase());
+        let api_url = format!("{}/profile/wow/character/{}/{}{}", self.api_url(region), realm, name, endpoint);
+        let namespace = format!("profile-{}", region);
+        let cache_key = format!("{}?namespace={}", api_url, namespace);
+
+        let text = match self.cache.as_ref().and_then(|x| x.get(&cache_key, kind)) {
+            Some(text) => text,
+            None => {
+                let token = self.access_token().await?;
+                let client = http_client::client();
+                let request = client
+                    .get(&api_url)
+                    .bearer_auth(token)
+                    .query(&[("namespace", namespace.as_str()), ("locale", "en_US")]);
+                let response = match client.send(request).await.and_then(|x| x.error_for_status()) {
+                    Ok(response) => response.text().await,
+                    Err(err) => Err(err),
+                };
+
+                match response {
+                    Ok(text) => {
+                        if let Some(cache) = &self.cache {
+                            cache.store(&cache_key, &text);
+                        }
+                        text
+                    },
+                    Err(err) => {
+                        error!("Error getting Blizzard API response for {}: {:?}", api_url, err);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        match serde_json::from_str(&text) {
+            Ok(response) => Some(response),
+            Err(err) => {
+                error!("Error parsing Blizzard API response for {}: {:?}", api_url, err);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CharacterSource for BlizzardApiSource {
+    async fn character(&self, url: &str) -> Option<ArmoryCharacterResponse> {
+        let (profile, equipment, encounters) = tokio::join!(
+            self.get::<ApiProfile>(url, "", PageKind::Profile),
+            self.get::<ApiEquipment>(url, "/equipment", PageKind::Profile),
+            self.get::<ApiRaidEncounters>(url, "/encounters/raids", PageKind::Profile)
+        );
+        let (profile, equipment) = (profile?, equipment?);
+        // Characters that never set foot in a raid return 404 here.
+        let encounters = encounters.unwrap_or_default();
+
+        let mut gear = HashMap::new();
+        for item in equipment.equipped_items {
+            gear.insert(item.slot.kind.to_lowercase().replace('_', ""), CharacterGear {
+                bonus_list: item.bonus_list,
+                enchantments: item.enchantments,
+                id: item.item.id as i32,
+                inventory_type: item.inventory_type,
+                sockets: item.sockets,
+            });
+        }
+
+        let character = ArmoryCharacter {
+            average_item_level: profile.equipped_item_level,
+            gear,
+            last_updated_timestamp: ArmoryTimestamp { epoch: profile.last_login_timestamp },
+            level: profile.level,
+        };
+        Some(ArmoryCharacterResponse::new(character, raid_summary(&self.raids, &encounters)))
+    }
+
+    async fn achievements(&self, url: &str) -> Option<ArmoryCharacterAchievementResponse> {
+        let api: ApiAchievements = self.get(url, "/achievements", PageKind::Achievements).await?;
+        let achievements = api.achievements.into_iter()
+            .filter(|x| x.completed_timestamp.is_some())
+            .map(|x| Achievements { account_wide: false, description: String::new(), id: x.id, name: x.achievement.name })
+            .collect();
+
+        // The progress checker only looks at the "raids" category and finds achievements by id, so everything earned goes in there.
+        let subcategories = HashMap::from([("raids".to_owned(), AchievementSubCategory { achievements, id: "raids".to_owned(), name: "Raids".to_owned() })]);
+        Some(ArmoryCharacterAchievementResponse { achievement_category: AchievementCategory { subcategories } })
+    }
+
+    async fn reputations(&self, url: &str) -> Option<ArmoryCharacterReputationResponse> {
+        let api: ApiReputations = self.get(url, "/reputations", PageKind::Reputation).await?;
+        let reputations = api.reputations.into_iter().map(|x| ReputationCategory {
+            id: faction_slug(&x.faction.name),
+            name: x.faction.name,
+            max: x.standing.max > 0 && x.standing.value >= x.standing.max,
+            max_value: Some(x.standing.max),
+            standing: Some(match x.standing.renown_level {
+                Some(level) => format!("Renown {}", level),
+                None => x.standing.name,
+            }),
+            value: Some(x.standing.value),
+            standing_type: None,
+            reputations: Vec::new(),
+        }).collect();
+
+        let region = armory_character_path(url).split('/').nth(1).unwrap_or_default().to_string();
+        Some(ArmoryCharacterReputationResponse {
+            reputations: ReputationsResponse {
+                region,
+                reputations: vec![ReputationCategory {
+                    id: "all".to_owned(),
+                    name: "All".to_owned(),
+                    max: false,
+                    max_value: None,
+                    standing: None,
+                    value: None,
+                    standing_type: None,
+                    reputations,
+                }],
+            },
+        })
+    }
+
+    async fn search(&self, url: &str) -> Option<String> {
+        self.armory.search(url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::expansion_config::{Expansion, ExpansionSeasons}, http_client::{block_on, test_server::{self, MockResponse}}};
+
+    static CHARACTER_URL: &str = "https://worldofwarcraft.blizzard.com/en-gb/character/eu/silvermoon/tester/";
+
+    fn expansions() -> ExpansionsConfig {
+        let raid = |id: i32, identifier: &str, bosses: &[&str]| ExpansionRaid {
+            id,
+            identifier: identifier.to_owned(),
+            boss_names: bosses.iter().map(|x| x.to_string()).collect(),
+            ..ExpansionRaid::default()
+        };
+
+        ExpansionsConfig {
+            latest_expansion: Some(Expansion {
+                seasons: vec![ExpansionSeasons {
+                    raids: vec![raid(1, "Second Raid", &["First Boss", "Last Boss"]), raid(0, "First Raid", &["Only Boss"])],
+                    ..ExpansionSeasons::default()
+                }],
+                ..Expansion::default()
+            }),
+            ..ExpansionsConfig::default()
+        }
+    }
+
+    fn mock_response(path: &str) -> (&'static str, &'static str) {
+        match path {
+            "/token" => ("200 OK", r#"{"access_token":"test-token","token_type":"bearer","expires_in":86399}"#),
+            "/profile/wow/character/silvermoon/tester" => ("200 OK", r#"{"name":"Tester","level":80,"average_item_level":642,"equipped_item_level":640,"last_login_timestamp":1741957200000}"#),
+            "/profile/wow/character/silvermoon/tester/equipment" => ("200 OK", r#"{"equipped_items":[
+                {"item":{"id":212000},"slot":{"type":"MAIN_HAND","name":"Main Hand"},"inventory_type":{"type":"TWOHWEAPON","name":"Two-Hand"},"enchantments":[{"enchantment_id":7460,"display_string":"Enchanted"}],"bonus_list":[1,2]},
+                {"item":{"id":212001},"slot":{"type":"FINGER_1","name":"Ring 1"},"inventory_type":{"type":"FINGER","name":"Finger"},"sockets":[{"socket_type":{"type":"PRISMATIC"},"item":{"id":213746}}]}]}"#),
+            "/profile/wow/character/silvermoon/tester/encounters/raids" => ("200 OK", r#"{"expansions":[{"expansion":{"name":"Test"},"instances":[{"instance":{"name":"Second Raid","id":1},"modes":[
+                {"difficulty":{"type":"HEROIC","name":"Heroic"},"status":{"type":"IN_PROGRESS"},"progress":{"completed_count":1,"total_count":2,"encounters":[{"encounter":{"name":"First Boss","id":1},"completed_count":3,"last_kill_timestamp":1741957200000}]}}]}]}]}"#),
+            "/profile/wow/character/silvermoon/tester/achievements" => ("200 OK", r#"{"achievements":[{"id":41297,"achievement":{"id":41297,"name":"Ahead of the Curve"},"completed_timestamp":1741957200000},{"id":41298,"achievement":{"id":41298,"name":"Cutting Edge"}}]}"#),
+            "/profile/wow/character/silvermoon/tester/reputations" => ("200 OK", r#"{"reputations":[{"faction":{"name":"Manaforge Vandals","id":2736},"standing":{"raw":12000,"value":1200,"max":2500,"tier":0,"name":"Renown 5","renown_level":5}}]}"#),
+            _ => ("404 Not Found", "{}"),
+        }
+    }
+
+    fn start_mock_server() -> String {
+        test_server::start(|request| {
+            let authorized = request.headers.iter().any(|x| x.starts_with("authorization:") && (x.contains("bearer test-token") || x.contains("basic")));
+            let (status, body) = if authorized { mock_response(&request.path) } else { ("401 Unauthorized", "{}") };
+            MockResponse::new(status, body)
+        })
+    }
+
+    fn source() -> BlizzardApiSource {
+        let address = start_mock_server();
+        let settings = BlizzardApiSettings {
+            client_id: "id".to_owned(),
+            client_secret: "secret".to_owned(),
+            api_url: address.clone(),
+            oauth_url: format!("{}/token", address),
+        };
+        BlizzardApiSource::new(settings, &expansions(), None)
+    }
+
+    #[test]
+    fn faction_slug_matches_armory() {
+        assert_eq!(faction_slug("Manaforge Vandals"), "manaforge-vandals");
+        assert_eq!(faction_slug("Gallagio Loyalty Rewards Club"), "gallagio-loyalty-rewards-club");
+        assert_eq!(faction_slug("Council of Dornogal"), "council-of-dornogal");
+        assert_eq!(faction_slug("The K'aresh Trust"), "the-karesh-trust");
+    }
+
+    #[test]
+    fn character_maps_into_armory_response() {
+        let response = block_on(source().character(CHARACTER_URL)).unwrap();
+        assert_eq!(response.character.level, 80);
+        assert_eq!(response.character.average_item_level, 640);
+        assert_eq!(response.character.last_updated_timestamp.epoch, 1741957200000);
+
+        let weapon = &response.character.gear["mainhand"];
+        assert_eq!(weapon.id, 212000);
+        assert_eq!(weapon.inventory_type.gear_type, "TWOHWEAPON");
+        assert_eq!(weapon.enchantments.as_ref().unwrap()[0].enchantment_id, 7460);
+        assert_eq!(response.character.gear["finger1"].sockets.as_ref().unwrap()[0].item.as_ref().unwrap().id, 213746);
+
+        // Indexed by raid id, with every difficulty present even when never entered.
+        assert_eq!(response.summary.raids.len(), 2);
+        assert_eq!(response.summary.raids[0].name, "First Raid");
+        assert_eq!(response.summary.raids[0].difficulties.len(), 4);
+        assert_eq!(response.summary.raids[0].difficulties[1].bosses[0].kill_count, 0);
+
+        let heroic = &response.summary.raids[1].difficulties[2];
+        assert_eq!(heroic.name, "Heroic");
+        assert_eq!(heroic.count, 1);
+        assert_eq!(heroic.bosses[0].kill_count, 3);
+        assert_eq!(heroic.bosses[0].last_timestamp, Some(1741957200000));
+        assert_eq!(heroic.bosses[1].kill_count, 0);
+    }
+
+    #[test]
+    fn achievements_only_include_completed() {
+        let response = block_on(source().achievements(CHARACTER_URL)).unwrap();
+        let raids = &response.achievement_category.subcategories["raids"];
+        assert_eq!(raids.achievements.len(), 1);
+        assert_eq!(raids.achievements[0].id, 41297);
+    }
+
+    #[test]
+    fn reputations_use_armory_slugs_and_renown() {
+        let response = block_on(source().reputations(CHARACTER_URL)).unwrap();
+        assert_eq!(response.reputations.region, "eu");
+        let rep = &response.reputations.reputations[0].reputations[0];
+        assert_eq!(rep.id, "manaforge-vandals");
+        assert_eq!(rep.standing.as_deref(), Some("Renown 5"));
+        assert_eq!(rep.value, Some(1200));
+    }
+
+    #[test]
+    fn unknown_character_is_none() {
+        let source = source();
+        assert!(block_on(source.character("https://worldofwarcraft.blizzard.com/en-gb/character/eu/silvermoon/nobody/")).is_none());
+    }
+}
@@ -1,8 +1,6 @@
 use std::collections::BTreeMap;
 use anyhow::{Context, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
-use regex::Regex;
-use reqwest::blocking::Client;
 use tracing::info;
 use crate::{checker::armory_checker::ArmoryCharacterReputationResponse, config::{expansion_config::ExpansionsConfig, settings::{RequiredRaid, RequiredRaidDifficulty}}};
 
@@ -10,7 +8,7 @@ pub struct BuffChecker {}
 
 impl BuffChecker {
     pub fn check_raids(
-        _url: String,
+        reputations: Option<&ArmoryCharacterReputationResponse>,
         expansions: &ExpansionsConfig,
         raid_saved_check_input: &BTreeMap<i32, RequiredRaid>,
     ) -> Result<BTreeMap<i32, (String, i32, bool, i32, i32)>> {
@@ -46,25 +44,7 @@ impl BuffChecker {
             return Ok(BTreeMap::new());
         }
         
-        let url = format!("{}/reputation", _url.trim_end_matches('/'));
-        let client = Client::new();
-        let response_text = client
-            .get(url)
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36")
-            .send()
-            .context("Failed to send request to armory")?
-            .text()
-            .context("Failed to read armory response text")?;
-    
-        let re = Regex::new(r#"var\s+characterProfileInitialState\s*=\s*(\{.*?\});"#)?;
-        let js_variable = re
-            .captures(&response_text)
-            .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str())
-            .context("Could not find 'characterProfileInitialState' JSON in the response")?;
-        
-        let armory_response: ArmoryCharacterReputationResponse = serde_json::from_str(js_variable)
-            .context("Failed to deserialize character JSON data")?;
+        let armory_response = reputations.context("Could not get the character's reputation data")?;
     
         let mut raid_buffs = BTreeMap::new();
         let all_reputations: Vec<_> = armory_response.reputations.reputations
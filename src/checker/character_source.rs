@@ -0,0 +1,291 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use tracing::{error, info};
+
+use crate::{http_client, checker::{armory_checker::{ArmoryCharacterAchievementResponse, ArmoryCharacterReputationResponse, ArmoryCharacterResponse}, blizzard_api::BlizzardApiSource, response_cache::{PageKind, ResponseCache}}, config::{expansion_config::ExpansionsConfig, settings::{DataSource, Settings}}};
+
+// Everything the checkers need to know about a character, keyed by its armory URL.
+#[async_trait]
+pub trait CharacterSource: Send + Sync {
+    async fn character(&self, url: &str) -> Option<ArmoryCharacterResponse>;
+    async fn achievements(&self, url: &str) -> Option<ArmoryCharacterAchievementResponse>;
+    async fn reputations(&self, url: &str) -> Option<ArmoryCharacterReputationResponse>;
+    // Raw HTML of an armory search page.
+    async fn search(&self, url: &str) -> Option<String>;
+}
+
+// force_refresh bypasses cached responses, but still stores the fresh ones.
+pub fn from_settings(settings: &Settings, expansions: &ExpansionsConfig, force_refresh: bool) -> Arc<dyn CharacterSource> {
+    let recordings = PathBuf::from(&settings.recordings_dir);
+    let cache = ResponseCache::new(&settings.cache, force_refresh);
+    match settings.data_source {
+        DataSource::Armory => Arc::new(ArmorySource::new(if settings.record_responses { Some(recordings) } else { None }, cache)),
+        DataSource::Replay => Arc::new(ReplaySource::new(recordings)),
+        DataSource::BlizzardApi => Arc::new(BlizzardApiSource::new(settings.blizzard_api.clone(), expansions, cache)),
+    }
+}
+
+pub fn achievements_url(url: &str) -> String {
+    format!("{}/achievements/feats-of-strength", url.trim_end_matches('/'))
+}
+
+pub fn reputation_url(url: &str) -> String {
+    format!("{}/reputation", url.trim_end_matches('/'))
+}
+
+// Recordings are stored flat, one file per URL, so a bundle can be zipped up and attached to a bug report.
+pub fn recording_file_name(url: &str) -> String {
+    let url = url.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+    let name: String = url.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' }).collect();
+    format!("{}.html", name)
+}
+
+fn parse_initial_state<T: DeserializeOwned>(page: &str, url: &str) -> Option<T> {
+    let re = Regex::new(r#"var\s+characterProfileInitialState\s*=\s*(\{.*?\});"#).unwrap();
+    let Some(captures) = re.captures(page) else {
+        error!("Could not find characterProfileInitialState for {}", url);
+        return None;
+    };
+
+    match serde_json::from_str(&captures[1]) {
+        Ok(response) => Some(response),
+        Err(err) => {
+            error!("Error parsing armory response for {}: {:?}", url, err);
+            None
+        }
+    }
+}
+
+pub struct ArmorySource {
+    record_dir: Option<PathBuf>,
+    cache: Option<ResponseCache>,
+}
+
+impl ArmorySource {
+    pub fn new(record_dir: Option<PathBuf>, cache: Option<ResponseCache>) -> Self {
+        if let Some(dir) = &record_dir {
+            if let Err(err) = fs::create_dir_all(dir) {
+                error!("Could not create recordings directory {:?}: {:?}", dir, err);
+            }
+        }
+
+        Self {
+            record_dir,
+            cache,
+        }
+    }
+
+    // Search pages are never cached, kind is None for those.
+    async fn fetch(&self, url: &str, kind: Option<PageKind>) -> Option<String> {
+        let cached = match (&self.cache, kind) {
+            (Some(cache), Some(kind)) => cache.get(url, kind),
+            _ => None,
+        };
+
+        let text = match cached {
+            Some(text) => text,
+            None => self.fetch_live(url, kind.is_some()).await?,
+        };
+
+        if let Some(dir) = &self.record_dir {
+            if let Err(err) = fs::write(dir.join(recording_file_name(url)), &text) {
+                error!("Could not record response for {}: {:?}", url, err);
+            }
+        }
+        Some(text)
+    }
+
+    async fn fetch_live(&self, url: &str, cacheable: bool) -> Option<String> {
+        let client = http_client::client();
+        let response = client.send(client.get(url)).await;
+
+        if response.is_err() {
+            error!("Error getting armory response: {:?}", response.err());
+            return None;
+        }
+
+        let response = response.unwrap();
+        let success = response.status().is_success();
+        let text = response.text().await;
+        if text.is_err() {
+            error!("Error getting armory response (text): {:?}", text.err());
+            return None;
+        }
+
+        let text = text.unwrap();
+        // Only successful pages are cached, an error page would otherwise stick around for the whole TTL.
+        if let Some(cache) = self.cache.as_ref().filter(|_| cacheable && success) {
+            cache.store(url, &text);
+        }
+        Some(text)
+    }
+}
+
+#[async_trait]
+impl CharacterSource for ArmorySource {
+    async fn character(&self, url: &str) -> Option<ArmoryCharacterResponse> {
+        parse_initial_state(&self.fetch(url, Some(PageKind::Profile)).await?, url)
+    }
+
+    async fn achievements(&self, url: &str) -> Option<ArmoryCharacterAchievementResponse> {
+        let url = achievements_url(url);
+        parse_initial_state(&self.fetch(&url, Some(PageKind::Achievements)).await?, &url)
+    }
+
+    async fn reputations(&self, url: &str) -> Option<ArmoryCharacterReputationResponse> {
+        let url = reputation_url(url);
+        parse_initial_state(&self.fetch(&url, Some(PageKind::Reputation)).await?, &url)
+    }
+
+    async fn search(&self, url: &str) -> Option<String> {
+        self.fetch(url, None).await
+    }
+}
+
+// Serves pages previously saved by ArmorySource, never touches the network.
+pub struct ReplaySource {
+    dir: PathBuf,
+}
+
+impl ReplaySource {
+    pub fn new(dir: PathBuf) -> Self {
+        info!("Replaying armory responses from {:?}", dir);
+        Self { dir }
+    }
+
+    fn fetch(&self, url: &str) -> Option<String> {
+        let path = self.dir.join(recording_file_name(url));
+        match fs::read_to_string(&path) {
+            Ok(text) => Some(text),
+            Err(_) => {
+                error!("No recording for {} (expected {:?})", url, path);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CharacterSource for ReplaySource {
+    async fn character(&self, url: &str) -> Option<ArmoryCharacterResponse> {
+        parse_initial_state(&self.fetch(url)?, url)
+    }
+
+    async fn achievements(&self, url: &str) -> Option<ArmoryCharacterAchievementResponse> {
+        let url = achievements_url(url);
+        parse_initial_state(&self.fetch(&url)?, &url)
+    }
+
+    async fn reputations(&self, url: &str) -> Option<ArmoryCharacterReputationResponse> {
+        let url = reputation_url(url);
+        parse_initial_state(&self.fetch(&url)?, &url)
+    }
+
+    async fn search(&self, url: &str) -> Option<String> {
+        self.fetch(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::mpsc};
+
+    use super::*;
+    use crate::{checker::{check_player::PlayerChecker, raid_sheet::Player}, http_client::block_on, config::{expansion_config::{Expansion, ExpansionsConfig}, realms::RealmJson, region::Region}};
+
+    static CHARACTER_URL: &str = "https://worldofwarcraft.blizzard.com/en-gb/character/eu/silvermoon/tester/";
+
+    fn replay_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("raidchecker_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn profile_page() -> String {
+        let state = r#"{"character":{"averageItemLevel":640,"gear":{},"lastUpdatedTimestamp":{"epoch":1741957200000},"level":80},
+            "summary":{"raids":[{"name":"Test Raid","difficulties":[{"name":"Normal","count":1,"total":1,"bosses":[{"killCount":2,"lastTimestamp":1741957200000,"name":"Test Boss"}]}]}]}}"#;
+        format!("<html><script>var characterProfileInitialState = {};</script></html>", state.replace('\n', ""))
+    }
+
+    #[test]
+    fn recording_file_name_is_flat() {
+        assert_eq!(recording_file_name(CHARACTER_URL), "worldofwarcraft.blizzard.com_en-gb_character_eu_silvermoon_tester.html");
+        assert_eq!(recording_file_name(&reputation_url(CHARACTER_URL)), "worldofwarcraft.blizzard.com_en-gb_character_eu_silvermoon_tester_reputation.html");
+    }
+
+    #[test]
+    fn replay_missing_recording_is_none() {
+        let source = ReplaySource::new(replay_dir("missing"));
+        assert!(block_on(source.character(CHARACTER_URL)).is_none());
+        assert!(block_on(source.search("https://worldofwarcraft.blizzard.com/en-gb/search?q=tester")).is_none());
+    }
+
+    #[test]
+    fn check_player_from_replay() {
+        let dir = replay_dir("check_player");
+        fs::write(dir.join(recording_file_name(CHARACTER_URL)), profile_page()).unwrap();
+        let source = ReplaySource::new(dir);
+
+        let expansions = ExpansionsConfig {
+            latest_expansion: Some(Expansion::default()),
+            ..ExpansionsConfig::default()
+        };
+
+        let player = Player {
+            name: "Tester".to_owned(),
+            className: "Tank".to_owned(),
+            userId: "1".to_owned(),
+            status: "primary".to_owned(),
+            ..Player::default()
+        };
+
+        let (thread_sender, _ui_receiver) = mpsc::channel();
+        let player_data = block_on(PlayerChecker::check_player(&player, &thread_sender, None, &Settings::default(), &expansions,
+            &RealmJson::new(Region::EU), &BTreeMap::new(), Some(CHARACTER_URL.to_owned()), &source)).unwrap();
+
+        assert!(player_data.skip_reason.is_none());
+        assert_eq!(player_data.ilvl, 640);
+        assert_eq!(player_data.lvl, 80);
+        assert_eq!(player_data.armory_url, CHARACTER_URL);
+
+        let boss = &player_data.raid_data[&0].bosses[&0].difficulties[&0];
+        assert!(boss.killed_before);
+        assert_eq!(boss.boss_kill_time, Some(1741957200000));
+    }
+
+    #[test]
+    fn deferred_check_collects_search_candidates() {
+        let settings = Settings::default();
+        let max_level = Expansion::default().max_lvl;
+        let dir = replay_dir("needs_attention");
+        let result = |realm: &str| format!(r#"<a class="Link Character" href="/character/eu/{}/tester"><div class="Character-name">Tester</div><div class="Character-level">{} Warrior</div><div class="Character-realm">{}</div></a>"#, realm, max_level, realm);
+        fs::write(dir.join(recording_file_name(&settings.current_preset.region.search_url("Tester"))),
+            format!("<html>{}{}</html>", result("silvermoon"), result("kazzak"))).unwrap();
+        let source = ReplaySource::new(dir);
+
+        let expansions = ExpansionsConfig {
+            latest_expansion: Some(Expansion::default()),
+            ..ExpansionsConfig::default()
+        };
+        let player = Player {
+            name: "Tester".to_owned(),
+            status: "primary".to_owned(),
+            ..Player::default()
+        };
+
+        let (thread_sender, ui_receiver) = mpsc::channel();
+        let player_data = block_on(PlayerChecker::check_player(&player, &thread_sender, None, &settings, &expansions,
+            &RealmJson::new(Region::EU), &BTreeMap::new(), None, &source)).unwrap();
+
+        assert_eq!(player_data.skip_reason.as_deref(), Some("Needs attention"));
+        let needs_attention = player_data.needs_attention.unwrap();
+        assert_eq!(needs_attention.candidates.len(), 2);
+        assert_eq!(needs_attention.reason, "Several characters match this name.");
+        // Nothing was asked of the UI.
+        assert!(ui_receiver.try_recv().is_err());
+    }
+}
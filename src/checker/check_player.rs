@@ -1,15 +1,16 @@
-use std::sync::{Arc, Mutex}; 
-use std::sync::mpsc::{Receiver, Sender}; 
+use std::sync::Arc; 
+use std::sync::mpsc::Sender; 
 
 
 use std::{collections::BTreeMap};
 
+use chrono::Utc;
 use regex::Regex;
-use reqwest::blocking::Client;
 use scraper::{Html, Selector};
+use tokio::sync::{Mutex, mpsc::UnboundedReceiver};
 use tracing::info;
 use strsim::jaro_winkler;
-use crate::{checker::{armory_checker::{ArmoryCharacter, PlayerRaidData}, buff_checker::BuffChecker, progress_checker::ProgressChecker, saved_checker::SavedChecker}, config::{self, realms::RealmJson, settings::RequiredRaid}};
+use crate::{checker::{armory_checker::{ArmoryCharacter, ArmoryCharacterAchievementResponse, ArmoryCharacterReputationResponse, ArmoryCharacterResponse, PlayerRaidData}, buff_checker::BuffChecker, character_source::CharacterSource, progress_checker::ProgressChecker, readiness::Readiness, rule_checker::PlayerIssue, saved_checker::SavedChecker}, config::{self, realms::RealmJson, region::Region, requirements::Requirements, settings::RequiredRaid}};
 
 use super::{armory_checker::{RaidProgressStatus, ArmoryChecker}, raid_sheet::{Player, RaidHelperCheckerStatus, RaidHelperUIStatus}};
 
@@ -17,19 +18,23 @@ pub struct PlayerChecker {}
 
 fn converted_name_correct_realm(ourl: String, realms: &RealmJson) -> String {
     info!("Converting name to correct realm slug: {}", ourl);
-    let mut url = ourl.to_lowercase();
-    let realm_name = url.split_once("/").unwrap().0;
+    let url = ourl.to_lowercase();
+    let Some((realm_name, char_name)) = url.split_once("/") else {
+        return url;
+    };
 
     let highest_realm = realms.realms.iter().map(|realm| {
-        let score = jaro_winkler(&realm.name.to_lowercase(), &realm_name);
+        let score = realm.names.iter().map(|name| jaro_winkler(name, realm_name)).fold(0.0, f64::max);
         (realm.slug.clone(), score)
-    }).max_by(|a, b| a.1.partial_cmp(&b.1).unwrap()).unwrap();
-
-    if !highest_realm.0.is_empty() {
-        info!("Realm match: {} with score {}", highest_realm.0, highest_realm.1);
-        url = url.replace(&realm_name, &highest_realm.0);
+    }).max_by(|a, b| a.1.total_cmp(&b.1));
+
+    match highest_realm {
+        Some((slug, score)) if !slug.is_empty() => {
+            info!("Realm match: {} with score {}", slug, score);
+            format!("{}/{}", slug, char_name)
+        },
+        _ => url,
     }
-    return url;
 }
 
 
@@ -97,29 +102,186 @@ pub struct PlayerData {
     pub class_name: String,
     #[serde(default)]
     pub role_name: String,
+    #[serde(default)]
+    pub wow_class: String, // From the event's template mapping, empty if the template doesn't say
+
+    #[serde(default="default_state", skip_deserializing)] // Loaded players are always refreshed, their issues aren't saved
+    pub dirty_state: i32,
+
+    #[serde(default)]
+    pub needs_attention: Option<NeedsAttention>,
+    #[serde(default)]
+    pub spec_name: String,
+    #[serde(default)]
+    pub checked_at: i64, // Unix timestamp, 0 for data saved before this was recorded
+    #[serde(default)]
+    pub checked_for: String, // What the result was checked against, see raid_sheet::check_context
+
+    // Worked out with the gear results whenever the settings change, see dirty_state.
+    #[serde(skip)]
+    pub issues: Vec<PlayerIssue>,
+    #[serde(skip)]
+    pub readiness: Option<Readiness>,
+}
+
+impl PlayerData {
+    // The WoW class if the event's template mapping knows it, otherwise whatever they signed up as.
+    pub fn display_class(&self) -> &str {
+        if self.wow_class.is_empty() {
+            &self.class_name
+        } else {
+            &self.wow_class
+        }
+    }
+
+    // Whether the result came from the armory, skipped players and ones still needing attention don't count.
+    pub fn was_checked(&self) -> bool {
+        self.skip_reason.is_none() && self.needs_attention.is_none()
+    }
+
+    // The preset's requirements with the overrides for this player's role, class and spec applied.
+    pub fn requirements<'a>(&self, preset: &'a config::settings::Preset) -> Requirements<'a> {
+        preset.requirements_for(&self.role_name, self.display_class(), &self.spec_name)
+    }
+}
 
-    #[serde(default="default_state")]
-    pub dirty_state: i32
+#[cfg(test)]
+impl PlayerData {
+    // A checked player with nothing known about them, tests fill in what they need.
+    pub fn for_test(discord_id: &str) -> Self {
+        Self {
+            discord_id: discord_id.to_owned(),
+            name: discord_id.to_owned(),
+            status: String::new(),
+            bad_gear: Vec::new(),
+            bad_socket: Vec::new(),
+            bad_special_item: Vec::new(),
+            character: ArmoryCharacter::default(),
+            num_embelishments: 0,
+            raid_data: BTreeMap::new(),
+            ilvl: 0,
+            lvl: 0,
+            aotc_status: BTreeMap::new(),
+            buff_status: BTreeMap::new(),
+            tier_count: 0,
+            pvp_gear: false,
+            skip_reason: None,
+            armory_url: String::new(),
+            queued: false,
+            confirmed: 0,
+            class_name: String::new(),
+            role_name: String::new(),
+            wow_class: String::new(),
+            dirty_state: -1,
+            needs_attention: None,
+            spec_name: String::new(),
+            checked_at: 0,
+            checked_for: String::new(),
+            issues: Vec::new(),
+            readiness: None,
+        }
+    }
+}
+
+// A sign-up that couldn't be matched to a character without asking, left for the user to resolve once the check is done.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NeedsAttention {
+    pub reason: String,
+    pub candidates: Vec<(String, String)>, // Label, armory URL
+    #[serde(default)]
+    pub low_level: bool,
+}
+
+impl NeedsAttention {
+    fn add(&mut self, candidates: Vec<(String, String)>, low_level: bool) {
+        for candidate in candidates {
+            if !self.candidates.iter().any(|x| x.1 == candidate.1) {
+                self.candidates.push(candidate);
+            }
+        }
+        self.low_level |= low_level;
+        self.reason = if !self.candidates.is_empty() {
+            "Several characters match this name.".to_owned()
+        } else if self.low_level {
+            "Only found low level characters with this name.".to_owned()
+        } else {
+            "No character found with this name.".to_owned()
+        };
+    }
 }
 
 enum SearchPromptResult {
     Url(String),
     Error(String),
     Skipped,
+    Deferred(Vec<(String, String)>, bool), // Candidates and whether low level characters were left out
+}
+
+// Everything fetched for one character. The three pages are independent, so they're requested together.
+struct CharacterPages {
+    profile: ArmoryCharacterResponse,
+    achievements: Option<ArmoryCharacterAchievementResponse>,
+    reputations: Option<ArmoryCharacterReputationResponse>,
+}
+
+async fn fetch_character(source: &dyn CharacterSource, url: &str) -> Option<CharacterPages> {
+    let (profile, achievements, reputations) = tokio::join!(source.character(url), source.achievements(url), source.reputations(url));
+    Some(CharacterPages {
+        profile: profile?,
+        achievements,
+        reputations,
+    })
+}
+
+// Kept apart from search_prompt, the parsed document can't be held across an await.
+fn parse_search_results(text: &str, region: Region, max_level: Option<u8>) -> (Vec<(String, String)>, bool) {
+    let mut low_level = false;
+    let mut chars: Vec<(String, String)> = Vec::new();
+    let document = Html::parse_document(text);
+    let link_selector = Selector::parse("a.Link.Character").unwrap();
+
+    for element in document.select(&link_selector) {
+        let href = element.value().attr("href").unwrap_or("");
+        let name_selector = Selector::parse(".Character-name").unwrap();
+        let level_selector = Selector::parse(".Character-level").unwrap();
+        let realm_selector = Selector::parse(".Character-realm").unwrap();
+
+        let name = element.select(&name_selector).next().map(|n| n.text().collect::<Vec<_>>().join(" ")).unwrap_or_default();
+        let mut level = element.select(&level_selector).next().map(|l| l.text().collect::<Vec<_>>().join(" ")).unwrap_or_default();
+        
+        let class = if let Some(idx) = level.find(" ") {
+            level.split_off(idx)
+        } else {
+            String::new()
+        };
+        let realm = element.select(&realm_selector).next().map(|r| r.text().collect::<Vec<_>>().join(" ")).unwrap_or_default();
+
+        let fixed_href = (region.armory_url() + href).trim_end_matches('/').to_string();
+        if max_level.is_some() && level.parse::<u8>().is_ok() {
+            if max_level.unwrap() > level.parse::<u8>().unwrap() {
+                low_level = true;
+                continue;
+            }
+        }
+        chars.push((format!("{} {}{}, level {}", name, realm, class, level), fixed_href.to_string()));
+    }
+    (chars, low_level)
 }
 
 impl PlayerChecker {
-    pub fn check_player(
+    pub async fn check_player(
         player: &Player, 
         thread_sender: &Sender<RaidHelperCheckerStatus>, 
-        thread_receiver: &Arc<Mutex<Receiver<RaidHelperUIStatus>>>, // Changed to Arc<Mutex<...>>
+        thread_receiver: Option<&Arc<Mutex<UnboundedReceiver<RaidHelperUIStatus>>>>, // None leaves unresolved names in needs_attention rather than asking
         settings: &config::settings::Settings, 
         expansions: &config::expansion_config::ExpansionsConfig, 
         realms: &config::realms::RealmJson,
         raid_saved_check: &BTreeMap<i32, RequiredRaid>, 
-        char_url: Option<String>
+        char_url: Option<String>,
+        source: &dyn CharacterSource
     ) -> Option<PlayerData> {
         let mut armory_data = None;
+        let mut needs_attention = NeedsAttention::default();
 
         let mut url = String::default();
         if char_url.is_some() {
@@ -127,10 +289,11 @@ impl PlayerChecker {
         }
         
         if !url.is_empty() {
-            armory_data = ArmoryChecker::check_armory(&url.clone());
+            armory_data = fetch_character(source, &url).await;
         }
 
         let max_level = expansions.latest_expansion.as_ref().unwrap().max_lvl;
+        let region = settings.current_preset.region;
 
         let role_name = match player.roleName.as_ref().unwrap_or(&String::new()).as_str() {
             "Tanks" | "Tank" => "tank".to_string(),
@@ -156,64 +319,85 @@ impl PlayerChecker {
             pvp_gear: false,
             skip_reason: Some("Skipped by user.".to_owned()),
             armory_url: "".to_owned(),
-            queued: player.status.to_lowercase() != "primary" || player.className.to_lowercase() == "bench",
+            queued: settings.current_preset.signup_filter.is_queued(player),
             confirmed: 0,
             class_name: player.className.clone().to_lowercase(),
             role_name: role_name,
-            dirty_state: -1
+            wow_class: String::new(),
+            dirty_state: -1,
+            needs_attention: None,
+            spec_name: player.specName.clone().unwrap_or_default(),
+            checked_at: Utc::now().timestamp(),
+            checked_for: String::new(),
+            issues: Vec::new(),
+            readiness: None,
         };
 
         let processed_name = process_name(&player.name);
         let is_some = processed_name.is_some();
         if let Some(name) = processed_name {
             if name.0.contains("/") && armory_data.is_none() {
-                let proper_url = format!("/en-gb/character/eu/{}/", converted_name_correct_realm(name.0.clone(), realms));
-                let full_url = format!("https://worldofwarcraft.blizzard.com{}", proper_url.to_lowercase());
-                url = full_url;
-                armory_data = ArmoryChecker::check_armory(&url.clone());
+                let converted = converted_name_correct_realm(name.0.clone(), realms);
+                // Without a realm to go on the name is searched for like any other.
+                if let Some((realm_slug, char_name)) = converted.split_once("/") {
+                    url = region.character_url(realm_slug, char_name);
+                    armory_data = fetch_character(source, &url).await;
+                }
             } 
 
             if armory_data.is_none() {
-                let search_response = Self::search_prompt(&name.1.clone(), Some(player), thread_sender, thread_receiver, Some(max_level));
+                let search_response = Self::search_prompt(&name.1.clone(), Some(player), thread_sender, thread_receiver, Some(max_level), region, source).await;
                 match search_response {
                     SearchPromptResult::Url(search_url) => {
                         url = search_url;
-                        armory_data = ArmoryChecker::check_armory(&url.clone());
+                        armory_data = fetch_character(source, &url).await;
                     },
                     SearchPromptResult::Skipped => {
                         return Some(basic_player_data);
                     },
+                    SearchPromptResult::Deferred(candidates, low_level) => needs_attention.add(candidates, low_level),
                     _ => {}
                 }
             }
         }
 
         if armory_data.is_none() && is_some {
-            let search_response = Self::search_prompt(&player.name, Some(player), thread_sender, thread_receiver, Some(max_level));
+            let search_response = Self::search_prompt(&player.name, Some(player), thread_sender, thread_receiver, Some(max_level), region, source).await;
             match search_response {
                 SearchPromptResult::Url(search_url) => {
                     url = search_url;
-                    armory_data = ArmoryChecker::check_armory(&url.clone());
+                    armory_data = fetch_character(source, &url).await;
                 },
                 SearchPromptResult::Skipped => {
                     return Some(basic_player_data);
                 },
+                SearchPromptResult::Deferred(candidates, low_level) => needs_attention.add(candidates, low_level),
                 _ => return None
             }
         }
 
-        if armory_data.is_none() {
+        if armory_data.is_none() && thread_receiver.is_none() {
+            needs_attention.add(Vec::new(), false);
+            info!("Leaving {} for later: {}", player.name, needs_attention.reason);
+            return Some(PlayerData {
+                skip_reason: Some("Needs attention".to_owned()),
+                needs_attention: Some(needs_attention),
+                ..basic_player_data
+            });
+        }
+
+        if let Some(thread_receiver) = thread_receiver.filter(|_| armory_data.is_none()) {
             let name = {
-                let rx = thread_receiver.lock().unwrap();
+                let mut rx = thread_receiver.lock().await;
                 info!("Locking for receiver to prompt for name for player: {}", player.name);
-                Self::prompt_for_name(Some(player), thread_sender, &*rx, false)
+                Self::prompt_for_name(Some(player), thread_sender, &mut rx, false).await
             };
             if name.is_some() {
-                let search_response = Self::search_prompt(&name.clone().unwrap(), Some(player), thread_sender, thread_receiver, Some(max_level));
+                let search_response = Self::search_prompt(&name.clone().unwrap(), Some(player), thread_sender, Some(thread_receiver), Some(max_level), region, source).await;
                 match search_response {
                     SearchPromptResult::Url(search_url) => {
                         url = search_url;
-                        armory_data = ArmoryChecker::check_armory(&url.clone());
+                        armory_data = fetch_character(source, &url).await;
                     },
                     SearchPromptResult::Skipped => {
                         return Some(basic_player_data);
@@ -227,12 +411,13 @@ impl PlayerChecker {
 
         info!("------------------- Checking player {} -------------------", player.name);
         let mut raid_data: BTreeMap<usize, PlayerRaidData> = BTreeMap::new();
-        let data = armory_data.unwrap();
-        ArmoryChecker::check_raid_boss_kills(&data, &mut raid_data);
+        let pages = armory_data.unwrap();
+        let data = &pages.profile;
+        ArmoryChecker::check_raid_boss_kills(data, &mut raid_data);
         let ilvl = data.character.average_item_level;
-        SavedChecker::check_bosses(&data, &mut raid_data);
-        let aotc_report = ProgressChecker::check_aotc(url.clone(), &data, expansions, &raid_saved_check);
-        let buff_status = BuffChecker::check_raids(url.clone(), expansions, &raid_saved_check);
+        SavedChecker::check_bosses(data, &mut raid_data);
+        let aotc_report = ProgressChecker::check_aotc(pages.achievements.as_ref(), data, expansions, &raid_saved_check);
+        let buff_status = BuffChecker::check_raids(pages.reputations.as_ref(), expansions, &raid_saved_check);
         let buff_status = if buff_status.is_err() {
             BTreeMap::new()
         } else {
@@ -264,18 +449,25 @@ impl PlayerChecker {
             pvp_gear: false,
             skip_reason: None,
             armory_url: url.clone(),
-            queued: player.status.to_lowercase() != "primary" || player.className.to_lowercase() == "bench",
+            queued: settings.current_preset.signup_filter.is_queued(player),
             confirmed: 0,
             class_name: player.className.clone().to_lowercase(),
             role_name: role_name,
-            dirty_state: -1
+            wow_class: String::new(),
+            dirty_state: -1,
+            needs_attention: None,
+            spec_name: player.specName.clone().unwrap_or_default(),
+            checked_at: Utc::now().timestamp(),
+            checked_for: String::new(),
+            issues: Vec::new(),
+            readiness: None,
         })
     }
 
-    fn prompt_for_name(
+    async fn prompt_for_name(
         player: Option<&Player>, 
         thread_sender: &Sender<RaidHelperCheckerStatus>, 
-        receiver: &Receiver<RaidHelperUIStatus>, 
+        receiver: &mut UnboundedReceiver<RaidHelperUIStatus>, 
         low_level: bool
     ) -> Option<String> {
         if player.is_some() {
@@ -290,77 +482,46 @@ impl PlayerChecker {
             let _ = thread_sender.send(RaidHelperCheckerStatus::QuestionStringSkip(format!("Please input a name for this character..."))).unwrap();
         }
         
-        match receiver.recv().unwrap() {
-            RaidHelperUIStatus::AnswerStringSkip(answer) => answer,
+        match receiver.recv().await {
+            Some(RaidHelperUIStatus::AnswerStringSkip(answer)) => answer,
             _ => None
         }
     }
 
-    fn search_prompt(
+    async fn search_prompt(
         name: &String, 
         player: Option<&Player>, 
         thread_sender: &Sender<RaidHelperCheckerStatus>, 
-        thread_receiver: &Arc<Mutex<Receiver<RaidHelperUIStatus>>>, 
-        max_level: Option<u8>
+        thread_receiver: Option<&Arc<Mutex<UnboundedReceiver<RaidHelperUIStatus>>>>, 
+        max_level: Option<u8>,
+        region: Region,
+        source: &dyn CharacterSource
     ) -> SearchPromptResult {
-        let url = format!("https://worldofwarcraft.blizzard.com/en-gb/search?q={}", name);
-        let client = Client::new();
-        let mut low_level = false;
-
-        let response = client
-            .get(url)
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36")
-            .send();
-        
-        if response.is_err() {
-            return SearchPromptResult::Error("Could not fetch the search results.".to_string());
-        }
+        let url = region.search_url(name);
 
-        let text = response.unwrap().text();
-        if text.is_err() {
+        let text = source.search(&url).await;
+        if text.is_none() {
             return SearchPromptResult::Error("Could not fetch the search results.".to_string());
         }
 
-        let mut chars: Vec<(String, String)> = Vec::new();
-        let document = Html::parse_document(&text.unwrap());
-        let link_selector = Selector::parse("a.Link.Character").unwrap();
- 
-        for element in document.select(&link_selector) {
-            let href = element.value().attr("href").unwrap_or("");
-            let name_selector = Selector::parse(".Character-name").unwrap();
-            let level_selector = Selector::parse(".Character-level").unwrap();
-            let realm_selector = Selector::parse(".Character-realm").unwrap();
-
-            let name = element.select(&name_selector).next().map(|n| n.text().collect::<Vec<_>>().join(" ")).unwrap_or_default();
-            let mut level = element.select(&level_selector).next().map(|l| l.text().collect::<Vec<_>>().join(" ")).unwrap_or_default();
-            
-            let class = if let Some(idx) = level.find(" ") {
-                level.split_off(idx)
-            } else {
-                String::new()
-            };
-            let realm = element.select(&realm_selector).next().map(|r| r.text().collect::<Vec<_>>().join(" ")).unwrap_or_default();
-
-            let fixed_href = ("https://worldofwarcraft.blizzard.com/en-gb".to_owned() + href).trim_end_matches('/').to_string();
-            if max_level.is_some() && level.parse::<u8>().is_ok() {
-                if max_level.unwrap() > level.parse::<u8>().unwrap() {
-                    low_level = true;
-                    continue;
-                }
-            }
-            chars.push((format!("{} {}{}, level {}", name, realm, class, level), fixed_href.to_string()));
-        }
+        let (chars, low_level) = parse_search_results(&text.unwrap(), region, max_level);
 
         if chars.len() == 1 {
             return SearchPromptResult::Url(chars.last().unwrap().1.clone());
-        } else if chars.is_empty() {
+        }
+
+        let Some(thread_receiver) = thread_receiver else {
+            return SearchPromptResult::Deferred(chars, low_level);
+        };
+
+        if chars.is_empty() {
             let name = {
-                let rx = thread_receiver.lock().unwrap();
+                let mut rx = thread_receiver.lock().await;
                 info!("Locking for receiver to prompt for name for player: {}", name);
-                Self::prompt_for_name(player, thread_sender, &*rx, low_level)
+                Self::prompt_for_name(player, thread_sender, &mut rx, low_level).await
             };
             if name.is_some() {
-                return Self::search_prompt(&name.clone().unwrap(), player, thread_sender, thread_receiver, max_level);
+                return Box::pin(Self::search_prompt(&name.clone().unwrap(), player, thread_sender, Some(thread_receiver), max_level, region, source)).await;
             }
             return SearchPromptResult::Skipped;
         }
@@ -370,27 +531,28 @@ impl PlayerChecker {
             None => None
         };
 
-        let rx = thread_receiver.lock().unwrap();
+        let mut rx = thread_receiver.lock().await;
         info!("Locking for receiver to search for name for player: {}", name);
         let _ = thread_sender.send(RaidHelperCheckerStatus::Search((name.clone(), spec, chars))).unwrap();
         
-        let name_url = match rx.recv().unwrap() {
-            RaidHelperUIStatus::SearchResponse(answer) => Some(answer),
+        let name_url = match rx.recv().await {
+            Some(RaidHelperUIStatus::SearchResponse(answer)) => Some(answer),
 
-            RaidHelperUIStatus::SearchResponseNewName() => {
-                let name = Self::prompt_for_name(None, thread_sender, &*rx, false);
+            Some(RaidHelperUIStatus::SearchResponseNewName()) => {
+                let name = Self::prompt_for_name(None, thread_sender, &mut rx, false).await;
                 drop(rx);
                 if name.is_some() {
-                    match Self::search_prompt(&name.unwrap(), player, thread_sender, thread_receiver, max_level) {
+                    match Box::pin(Self::search_prompt(&name.unwrap(), player, thread_sender, Some(thread_receiver), max_level, region, source)).await {
                         SearchPromptResult::Url(url) => return SearchPromptResult::Url(url),
                         SearchPromptResult::Skipped => return SearchPromptResult::Skipped,
                         SearchPromptResult::Error(err) => return SearchPromptResult::Error(err),
+                        SearchPromptResult::Deferred(candidates, low_level) => return SearchPromptResult::Deferred(candidates, low_level),
                     }
                 }
                 None
             },
 
-            RaidHelperUIStatus::SearchResponseSkip() => {
+            Some(RaidHelperUIStatus::SearchResponseSkip()) => {
                 return SearchPromptResult::Skipped;
             }
             _ => None
@@ -402,4 +564,4 @@ impl PlayerChecker {
         
         SearchPromptResult::Url(name_url.unwrap().unwrap().1)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,101 @@
+use regex::Regex;
+
+// Turns whatever was pasted as an event into a raid-helper event ID. Raid-helper events are Discord messages and
+// share their ID, so a link to the event's message works as well as a raid-helper link or the bare ID.
+// Errors say why the paste couldn't be used.
+pub fn resolve_event_id(input: &str) -> Result<String, String> {
+    let mut ids: Vec<String> = Vec::new();
+    let mut first_error = None;
+    for token in input.split_whitespace() {
+        match resolve_token(token) {
+            Some(Ok(id)) if !ids.contains(&id) => ids.push(id),
+            Some(Err(err)) => {
+                first_error.get_or_insert(err);
+            },
+            _ => {},
+        }
+    }
+
+    match ids.len() {
+        1 => Ok(ids.remove(0)),
+        0 => Err(first_error.unwrap_or_else(|| "Paste a raid-helper event link, the event's Discord message link or its event ID.".to_owned())),
+        _ => Err(format!("That contains more than one event ({}), paste just the one to check.", ids.join(", "))),
+    }
+}
+
+// None for anything that doesn't look like an attempt at an event, such as words copied along with the link.
+fn resolve_token(token: &str) -> Option<Result<String, String>> {
+    // Discord wraps links in <> when embeds are suppressed, and punctuation tends to get copied along.
+    let token = token.trim_matches(|c: char| c == '<' || c == '>' || c == '(' || c == ')' || c == ',' || c == '.');
+    let token = token.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/');
+
+    let re_discord = Regex::new(r"(?i)^(?:https?://)?(?:(?:www|ptb|canary)\.)?discord(?:app)?\.com/channels/(\d+|@me)(?:/(\d+))?(?:/(\d+))?$").unwrap();
+    let re_raid_helper = Regex::new(r"(?i)^(?:https?://)?(?:www\.)?raid-helper\.(?:dev|xyz)(?:/(.*))?$").unwrap();
+    let re_raid_helper_path = Regex::new(r"(?i)^(?:api/(?:v\d+/)?)?(?:raidplan|event|events|comp|composition|comptool)/(\w+)$").unwrap();
+
+    if let Some(caps) = re_discord.captures(token) {
+        return Some(match caps.get(3) {
+            Some(message) => Ok(message.as_str().to_owned()),
+            None => Err("That's a link to a Discord channel, not an event. Right-click the event's message and use Copy Message Link.".to_owned()),
+        });
+    }
+
+    if let Some(caps) = re_raid_helper.captures(token) {
+        let path = caps.get(1).map(|x| x.as_str()).unwrap_or_default();
+        return Some(match re_raid_helper_path.captures(path) {
+            Some(caps) => Ok(caps[1].to_owned()),
+            None => Err("That raid-helper link doesn't point at an event, open the event or its composition and copy that link.".to_owned()),
+        });
+    }
+
+    if token.chars().all(|c| c.is_ascii_digit()) && !token.is_empty() {
+        // Discord IDs are 17 to 20 digits, anything shorter is more likely a date, time or sign-up count.
+        return Some(if (17..=20).contains(&token.len()) {
+            Ok(token.to_owned())
+        } else {
+            Err(format!("{} is too short to be an event ID, event IDs are the 17-20 digit ID of the event's Discord message.", token))
+        });
+    }
+
+    if token.to_lowercase().starts_with("http://") || token.to_lowercase().starts_with("https://") {
+        return Some(Err("Only raid-helper and Discord links can be checked.".to_owned()));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_raid_helper_form_resolves() {
+        let id = "1234567890123456789";
+        let pastes = [
+            format!("https://raid-helper.dev/raidplan/{id}"),
+            format!("https://raid-helper.xyz/event/{id}"),
+            format!("https://raid-helper.dev/api/v4/events/{id}"),
+            format!("raid-helper.dev/comp/{id}?view=roles"),
+            format!("https://discord.com/channels/111111111111111111/222222222222222222/{id}"),
+            format!("<https://ptb.discordapp.com/channels/111111111111111111/222222222222222222/{id}>"),
+            id.to_owned(),
+            format!("Tuesday raid: https://raid-helper.dev/event/{id} see you there"),
+            // The same event twice isn't ambiguous.
+            format!("{id} https://raid-helper.dev/event/{id}"),
+        ];
+        for paste in pastes.iter() {
+            assert_eq!(resolve_event_id(paste).as_deref(), Ok(id), "{}", paste);
+        }
+    }
+
+    #[test]
+    fn ambiguous_pastes_say_why() {
+        assert!(resolve_event_id("https://discord.com/channels/111111111111111111/222222222222222222").unwrap_err().contains("channel"));
+        assert!(resolve_event_id("https://raid-helper.dev/dashboard").unwrap_err().contains("doesn't point at an event"));
+        assert!(resolve_event_id("20:00").unwrap_err().contains("Paste"));
+        assert!(resolve_event_id("12345").unwrap_err().contains("too short"));
+        assert!(resolve_event_id("https://example.com/event/1234567890123456789").unwrap_err().contains("Only raid-helper"));
+        assert!(resolve_event_id("1234567890123456789 9876543210987654321").unwrap_err().contains("more than one event"));
+        assert!(resolve_event_id("   ").is_err());
+    }
+}
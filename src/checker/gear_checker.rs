@@ -2,12 +2,13 @@ use std::collections::HashMap;
 
 use tracing::{error, info};
 
-use crate::{checker::armory_checker::{ArmoryCharacter, CharacterGear}, config::{self, expansion_config::{Expansion, ExpansionsConfig, ItemData}, settings::{Settings, SlotSetting}}};
+use crate::{checker::armory_checker::{ArmoryCharacter, CharacterGear}, config::{self, expansion_config::{Expansion, ExpansionsConfig, ItemData}, settings::{SlotSetting, Slots}}};
 
 pub struct GearChecker;
 
 impl GearChecker {
-    pub fn check_gear(character: &ArmoryCharacter, settings: &config::settings::Settings, expansions: &config::expansion_config::ExpansionsConfig) -> (Vec<String>, Vec<String>, Vec<String>, i32) {
+    // Checked against the slot requirements the player's overrides give them, see `PlayerData::requirements`.
+    pub fn check_gear(character: &ArmoryCharacter, slots: &Slots, expansions: &config::expansion_config::ExpansionsConfig) -> (Vec<String>, Vec<String>, Vec<String>, i32) {
         let mut enchant_vec = Vec::new();
         let mut socket_vec = Vec::new();
         let mut special_item = Vec::new();
@@ -28,45 +29,21 @@ impl GearChecker {
                 }
             }
 
-            let mut enchantment_slot = expansion.slot_data.iter().find(|x| {
-                let mut mtch = x.slot == gear.1.inventory_type.gear_type.to_lowercase();
-                if mtch == false {
-                    mtch = x.sub_slots.iter().find(|y| **y == gear.1.inventory_type.gear_type.to_lowercase()).is_some();
-                }
-                mtch
-            });
-
-            if enchantment_slot.is_none() {
-                let target_type = gear.1.inventory_type.gear_type.to_lowercase();
-                enchantment_slot = expansion.latest_season.as_ref()
-                    .and_then(|season| {
-                        season.seasonal_slot_data.iter().find(|ench| {
-                            let mut matches = ench.slot == target_type;
-                            if !matches {
-                                matches = ench.sub_slots.iter().any(|sub_slot_ref| {
-                                    sub_slot_ref.as_str() == target_type
-                                });
-                            }
-                            matches
-                        })
-                    });
-            }
-
-            if enchantment_slot.is_some() {
+            if let Some(enchantment_slot) = Self::slot_data(&expansion, &gear.1) {
 
-                if (gear.0 == "offhand" && gear.1.inventory_type.gear_type.to_lowercase() == "weapon") || gear.0 != "offhand" {
-                    let str = Self::check_enchant_slot(&expansion, &gear.1, enchantment_slot.unwrap(), &settings, expansions);
+                if Self::checks_enchant(&gear.0, &gear.1) {
+                    let str = Self::check_enchant_slot(&expansion, &gear.1, enchantment_slot, slots, expansions);
                     if str.len() > 0 {
                         enchant_vec.push(str);
                     }
                 }
 
-                let str = Self::check_gear_socket(&expansions, &gear.1, enchantment_slot.unwrap(), &settings);
+                let str = Self::check_gear_socket(&expansions, &gear.1, enchantment_slot, slots);
                 if str.len() > 0 {
                     socket_vec.push(str);
                 }
 
-                let special = Self::check_special_item(&expansions, &gear.1, enchantment_slot.unwrap(), &settings);
+                let special = Self::check_special_item(&expansions, &gear.1, enchantment_slot, slots);
                 if special.len() > 0 {
                     special_item.push(special);
                 }
@@ -76,8 +53,27 @@ impl GearChecker {
     }
 
 
-    fn check_enchant_slot(expansion: &Expansion, gear: &CharacterGear, item: &ItemData, settings: &Settings, expansions: &config::expansion_config::ExpansionsConfig) -> String {
-        let binding = settings.current_preset.slots.as_array();
+    // The expansion's data for the slot the gear goes in, two-handers and the like are listed under the slot's sub slots.
+    // Slots the expansion has nothing for, like belts in some seasons, fall back to the season's.
+    pub fn slot_data<'a>(expansion: &'a Expansion, gear: &CharacterGear) -> Option<&'a ItemData> {
+        let target_type = gear.inventory_type.gear_type.to_lowercase();
+        let matches = |item: &&ItemData| item.slot == target_type || item.sub_slots.contains(&target_type);
+        expansion.slot_data.iter().find(matches)
+            .or_else(|| expansion.latest_season.as_ref().and_then(|season| season.seasonal_slot_data.iter().find(matches)))
+    }
+
+    // The slot settings for the expansion's slot data.
+    pub fn slot_setting(slots: &Slots, item: &ItemData) -> Option<SlotSetting> {
+        slots.as_array().into_iter().find(|x| x.1 == item.slot).map(|x| x.0)
+    }
+
+    // Off hands are only looked at for an enchant when they're a weapon.
+    pub fn checks_enchant(equipped_slot: &str, gear: &CharacterGear) -> bool {
+        equipped_slot != "offhand" || gear.inventory_type.gear_type.to_lowercase() == "weapon"
+    }
+
+    fn check_enchant_slot(expansion: &Expansion, gear: &CharacterGear, item: &ItemData, slots: &Slots, expansions: &config::expansion_config::ExpansionsConfig) -> String {
+        let binding = slots.as_array();
         let item_options_opt: Option<&(SlotSetting, &str)> = binding.iter().find(|x| {
             x.1 == item.slot
         });
@@ -174,13 +170,13 @@ impl GearChecker {
         return bad_str;
     }
 
-    fn check_gear_socket(expansions: &ExpansionsConfig, gear: &CharacterGear, item: &ItemData, settings: &Settings) -> String {
+    fn check_gear_socket(expansions: &ExpansionsConfig, gear: &CharacterGear, item: &ItemData, slots: &Slots) -> String {
         if expansions.latest_expansion.is_none() {
             error!("Latest expansion is referencing nothing!");
             return String::default();
         }
 
-        let binding = settings.current_preset.slots.as_array();
+        let binding = slots.as_array();
         let enchant_options_opt = binding.iter().find(|x| {
             x.1 == item.slot
         });
@@ -252,10 +248,10 @@ impl GearChecker {
         expansions: &ExpansionsConfig,
         gear: &CharacterGear,
         item: &ItemData,
-        settings: &Settings
+        slots: &Slots
     ) -> String {
     
-        let binding = settings.current_preset.slots.as_array();
+        let binding = slots.as_array();
         let Some((slot_setting, _)) = binding.iter().find(|(_, slot)| *slot == item.slot) else {
             return String::default();
         };
@@ -5,4 +5,14 @@ pub mod armory_checker;
 pub mod progress_checker;
 pub mod buff_checker;
 pub mod saved_checker;
-pub mod gear_checker;
\ No newline at end of file
+pub mod character_source;
+pub mod blizzard_api;
+pub mod response_cache;
+pub mod gear_checker;
+pub mod signup_watch;
+pub mod server_events;
+pub mod writeback;
+pub mod event_reference;
+pub mod signup_import;
+pub mod rule_checker;
+pub mod readiness;
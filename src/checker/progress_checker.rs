@@ -1,7 +1,5 @@
 use std::collections::BTreeMap;
 
-use regex::Regex;
-use reqwest::blocking::Client;
 use tracing::{error, info};
 use crate::{checker::armory_checker::{ArmoryCharacterAchievementResponse, ArmoryCharacterResponse, ArmoryRaids, RaidProgressStatus}, config::{self, expansion_config::RaidAchievements, settings::{RequiredRaid, RequiredRaidDifficulty}}};
 
@@ -9,7 +7,7 @@ pub struct ProgressChecker {}
 
 impl ProgressChecker {
     pub fn check_aotc(
-        url: String,
+        achievements: Option<&ArmoryCharacterAchievementResponse>,
         armory: &ArmoryCharacterResponse,
         expansions: &config::expansion_config::ExpansionsConfig,
         raid_saved_check_input: &BTreeMap<i32, RequiredRaid>,
@@ -18,20 +16,17 @@ impl ProgressChecker {
 
         let raid_saved_check = Self::determine_raids_to_check(expansions, raid_saved_check_input);
 
-        let feats_url = format!("{}/achievements/feats-of-strength", url.trim_end_matches('/'));
-        let response_text = Self::fetch_achievements(&feats_url);
-
         let mut aotc_ce_status = BTreeMap::new();
-        if let Some(data) = Self::extract_achievement_data(&response_text) {
+        if let Some(data) = achievements {
             Self::process_achievements(
-                &data,
+                data,
                 armory,
                 expansions,
                 &raid_saved_check,
                 &mut aotc_ce_status,
             );
         } else {
-            error!("Could not find character profile initial state in response.");
+            error!("No achievement data for this character.");
         }
 
         Self::fill_missing_raids(&raid_saved_check, expansions, &mut aotc_ce_status);
@@ -70,28 +65,6 @@ impl ProgressChecker {
         input.clone()
     }
 
-    fn fetch_achievements(url: &str) -> String {
-        let client = Client::new();
-        client
-            .get(url)
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36",
-            )
-            .send()
-            .and_then(|r| r.text())
-            .unwrap_or_else(|e| {
-                error!("Failed to fetch achievements: {}", e);
-                String::new()
-            })
-    }
-
-    fn extract_achievement_data(response: &str) -> Option<ArmoryCharacterAchievementResponse> {
-        let re = Regex::new(r#"var\s+characterProfileInitialState\s*=\s*(\{.*?\});"#).unwrap();
-        re.captures(response)
-            .and_then(|cap| serde_json::from_str(&cap[1]).ok())
-    }
-
     fn process_achievements(
         data: &ArmoryCharacterAchievementResponse,
         armory: &ArmoryCharacterResponse,
@@ -1,8 +1,8 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::mpsc::{self, Receiver}};
 
-use regex::Regex;
+use chrono::{Local, TimeZone};
 
-use crate::{checker::{check_player::PlayerChecker, raid_sheet::PlayerOnlyCheckType}, config::{self, expansion_config::ExpansionRaid, settings::{Settings, RequiredRaid, RequiredRaidDifficulty}}};
+use crate::{http_client, checker::{check_player::PlayerChecker, event_reference::resolve_event_id, raid_sheet::PlayerOnlyCheckType, server_events::{self, ServerEvent}}, config::{self, expansion_config::ExpansionRaid, settings::{Settings, RequiredRaid, RequiredRaidDifficulty}}};
 
 #[derive(PartialEq)]
 pub(crate) enum QuestionState {
@@ -16,46 +16,20 @@ pub struct RaidCheckQuestions {
     pub(crate) state: QuestionState,
     pub(crate) saved_bosses: BTreeMap<i32, RequiredRaid>,
     pub(crate) raid_helper_url: String,
-    raid_helper_url_error: bool,
+    raid_helper_url_error: Option<String>,
     pub(crate) ignore_url_question: bool,
     pub(crate) player_only: PlayerOnlyCheckType,
     display_raid_id: i32,
     display_difficulty_id: i32,
-}
-
-pub enum MatchType {
-    RaidPlan(String),
-    Event(String),
-    ApiV2Event(String),
-}
 
-pub fn get_url_match_type(url: &str) -> Option<MatchType> {
-    let re_raidplan = Regex::new(r"^https://raid-helper\.(dev|xyz)/raidplan/(\w+)$").unwrap();
-    let re_event = Regex::new(r"^https://raid-helper\.(dev|xyz)/event/(\w+)$").unwrap();
-    let re_api_v2_event = Regex::new(r"^https://raid-helper\.(dev|xyz)/api/v4/events/(\w+)$").unwrap();
-
-    if let Some(caps) = re_raidplan.captures(url) {
-        Some(MatchType::RaidPlan(caps[2].to_string()))
-    } else if let Some(caps) = re_event.captures(url) {
-        Some(MatchType::Event(caps[2].to_string()))
-    } else if let Some(caps) = re_api_v2_event.captures(url) {
-        Some(MatchType::ApiV2Event(caps[2].to_string()))
-    } else {
-        None
-    }
+    // Upcoming events of the Discord server set in the raid-helper settings.
+    server_events: Vec<ServerEvent>,
+    server_events_error: Option<String>,
+    server_events_receiver: Option<Receiver<Result<Vec<ServerEvent>, String>>>,
 }
 
-fn check_raidhelper_url(url: String) -> Option<String> {
-    match get_url_match_type(&url) {
-        Some(MatchType::RaidPlan(id)) |
-        Some(MatchType::Event(id)) => {
-            Some(format!("https://raid-helper.xyz/api/v4/events/{id}"))
-        }
-
-        Some(MatchType::ApiV2Event(_)) => Some(url),
-
-        _ => None,
-    }
+fn check_raidhelper_url(url: &str) -> Result<String, String> {
+    resolve_event_id(url).map(|id| format!("https://raid-helper.xyz/api/v4/events/{id}"))
 }
 
 impl Default for RaidCheckQuestions {
@@ -64,16 +38,76 @@ impl Default for RaidCheckQuestions {
             state: QuestionState::None,
             saved_bosses: BTreeMap::new(),
             raid_helper_url: String::default(),
-            raid_helper_url_error: false,
+            raid_helper_url_error: None,
             ignore_url_question: false,
             player_only: PlayerOnlyCheckType::None,
             display_raid_id: -1,
             display_difficulty_id: 0,
+            server_events: Vec::new(),
+            server_events_error: None,
+            server_events_receiver: None,
         }
     }
 }
 
 impl RaidCheckQuestions {
+    fn load_server_events(&mut self, settings: &Settings) {
+        let (sender, receiver) = mpsc::channel();
+        let raid_helper = settings.raid_helper.clone();
+        http_client::runtime().spawn(async move {
+            let _ = sender.send(server_events::fetch_server_events(&raid_helper).await);
+        });
+        self.server_events_receiver = Some(receiver);
+        self.server_events_error = None;
+    }
+
+    // Returns the check URL of the event that was clicked.
+    fn draw_server_events(&mut self, ui: &mut egui::Ui, settings: &mut Settings) -> Option<String> {
+        if let Some(result) = self.server_events_receiver.as_ref().and_then(|x| x.try_recv().ok()) {
+            self.server_events_receiver = None;
+            match result {
+                Ok(events) => self.server_events = events,
+                Err(err) => self.server_events_error = Some(err),
+            }
+        }
+
+        let mut picked = None;
+        egui::Grid::new("server_events_grid").show(ui, |ui| {
+            ui.label("Server ID");
+            ui.text_edit_singleline(&mut settings.raid_helper.server_id);
+            ui.end_row();
+
+            ui.label("API key");
+            ui.add(egui::TextEdit::singleline(&mut settings.raid_helper.api_key).password(true).hint_text("If needed"))
+                .on_hover_text("Only needed if the server doesn't share its events, use /apikey in Discord to get one.");
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(self.server_events_receiver.is_none(), egui::Button::new("Load events")).clicked() {
+                settings.save();
+                self.load_server_events(settings);
+            }
+            if self.server_events_receiver.is_some() {
+                ui.spinner();
+            }
+        });
+
+        if let Some(err) = &self.server_events_error {
+            ui.label(egui::RichText::new(err).color(egui::Color32::RED));
+        }
+
+        egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+            for event in self.server_events.iter() {
+                let date = Local.timestamp_opt(event.start_time, 0).single().map(|x| x.format("%a %d %b %H:%M").to_string()).unwrap_or_default();
+                if ui.button(format!("{} - {} ({} signed)", date, event.title, event.signup_count)).clicked() {
+                    picked = Some(server_events::event_url(&settings.raid_helper, &event.id));
+                }
+            }
+        });
+        picked
+    }
+
     pub fn ask_questions(&mut self, ctx: &eframe::egui::Context, expansion_config: &config::expansion_config::ExpansionsConfig, url: Option<String>, is_player: Option<PlayerOnlyCheckType>,
         settings: &mut Settings) -> Option<(String, BTreeMap<i32, RequiredRaid>, PlayerOnlyCheckType)> {
         let mut send_it: Option<(String, BTreeMap<i32, RequiredRaid>, PlayerOnlyCheckType)> = None;
@@ -218,7 +252,7 @@ impl RaidCheckQuestions {
                 if self.ignore_url_question && self.raid_helper_url.len() > 0 {
                     send_it = Some((self.raid_helper_url.clone(), self.saved_bosses.clone(), self.player_only.clone()));
                     self.saved_bosses.clear();
-                    self.raid_helper_url_error = false;
+                    self.raid_helper_url_error = None;
                     self.state = QuestionState::None;
                 }
                 else {
@@ -231,13 +265,25 @@ impl RaidCheckQuestions {
                         }
                         else {
                             ui.label("Please input the raid-helper URL that contains the signed characters you want to check.");
+                            ui.label("The event's Discord message link, a composition link or the bare event ID work too.");
                         }
 
                         let response = ui.text_edit_singleline(&mut self.raid_helper_url);
                         let pressed_enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
 
-                        if self.raid_helper_url_error == true {
-                            ui.label(egui::RichText::new("Invalid Raid Helper URL!").color(egui::Color32::RED));
+                        if let Some(err) = &self.raid_helper_url_error {
+                            ui.label(egui::RichText::new(err).color(egui::Color32::RED));
+                        }
+
+                        if self.player_only != PlayerOnlyCheckType::Player {
+                            let picked = ui.collapsing("Pick from a Discord server", |ui| self.draw_server_events(ui, settings)).body_returned.flatten();
+                            if let Some(url) = picked {
+                                self.raid_helper_url = url;
+                                send_it = Some((self.raid_helper_url.clone(), self.saved_bosses.clone(), self.player_only.clone()));
+                                self.saved_bosses.clear();
+                                self.raid_helper_url_error = None;
+                                self.state = QuestionState::None;
+                            }
                         }
 
                         ui.horizontal(|ui| {
@@ -247,20 +293,20 @@ impl RaidCheckQuestions {
                                 if self.player_only == PlayerOnlyCheckType::Player {
                                     send_it = Some((self.raid_helper_url.clone(), self.saved_bosses.clone(), self.player_only.clone()));
                                     self.saved_bosses.clear();
-                                    self.raid_helper_url_error = false;
+                                    self.raid_helper_url_error = None;
                                     self.state = QuestionState::None;
                                 }
                                 else {
-                                    if let Some(url) = check_raidhelper_url(self.raid_helper_url.clone()) {
-                                        self.raid_helper_url = url;
-                                        
-                                        send_it = Some((self.raid_helper_url.clone(), self.saved_bosses.clone(), self.player_only.clone()));
-                                        self.saved_bosses.clear();
-                                        self.raid_helper_url_error = false;
-                                        self.state = QuestionState::None;
-                                        
-                                    } else {
-                                        self.raid_helper_url_error = true;
+                                    match check_raidhelper_url(&self.raid_helper_url) {
+                                        Ok(url) => {
+                                            self.raid_helper_url = url;
+                                            
+                                            send_it = Some((self.raid_helper_url.clone(), self.saved_bosses.clone(), self.player_only.clone()));
+                                            self.saved_bosses.clear();
+                                            self.raid_helper_url_error = None;
+                                            self.state = QuestionState::None;
+                                        },
+                                        Err(err) => self.raid_helper_url_error = Some(err),
                                     }
                                 }
                             };
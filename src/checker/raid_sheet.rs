@@ -1,19 +1,21 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::sync::mpsc::Receiver; 
+use futures_util::{stream, StreamExt};
+use chrono::Utc;
 use regex::Regex;
-use reqwest::{blocking::Client, header::{ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONNECTION, HeaderMap, HeaderValue, USER_AGENT}};
+use reqwest::header::{ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONNECTION, HeaderMap, HeaderValue, USER_AGENT};
 use serde_json::{Deserializer, Value, from_value};
 use tracing::info;
-use std::{collections::BTreeMap, fs::File, io::{Read, Write}, sync::{atomic::{AtomicUsize, Ordering}, mpsc::{self, Sender}}, thread};
-use rayon::{ThreadPoolBuilder, prelude::*};
+use std::{collections::BTreeMap, fs::File, io::{Read, Write}, sync::{atomic::{AtomicUsize, Ordering}, mpsc}};
+use tokio::{sync::{Mutex, mpsc::{unbounded_channel, UnboundedSender}}, task::JoinHandle};
 
-use crate::{checker::armory_checker::ArmoryCharacter, config::{self, last_raid::LastRaid, settings::RequiredRaid}};
+use crate::{checker::armory_checker::ArmoryCharacter, config::{self, check_progress::{CheckProgress, ProgressWriter, CHECK_PROGRESS_FILE}, last_raid::LastRaid, settings::RequiredRaid, template_mapping::{TemplatesConfig, TEMPLATES_FILE}}, http_client};
 
-use super::check_player::{PlayerChecker, PlayerData};
+use super::{character_source, check_player::{PlayerChecker, PlayerData}, signup_import::{import_url, is_import_url, signups_from_results, IMPORT_URL_PREFIX}};
 
 // When called
 // Register async messagers
-// Everything happens in seperate thread
+// Everything happens on the shared tokio runtime, checking several players at once
 // UI calls are done to send data such as progress
 // WHen search is required we'll lock and await main thread to handle search UI
 
@@ -22,7 +24,7 @@ pub static RAID_PLAN_UNCONFIRMED: u8 = 2;
 pub static RAID_PLAN_CANCELLED: u8 = 1;
 pub static RAID_PLAN_NONE: u8 = 0;
 
-#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct Player {
     pub position: i32,
     pub specName: Option<String>,
@@ -39,13 +41,17 @@ struct Roles {
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
-struct RaidHelper {
-    signUps: Vec<Player>,
+pub(crate) struct RaidHelper {
+    pub(crate) signUps: Vec<Player>,
     #[serde(alias = "displayTitle")]
     name: String,
     id: String,
     templateId: Option<String>,
     roles: Option<Vec<Roles>>,
+    #[serde(rename = "startTime")]
+    start_time: Option<i64>,
+    #[serde(default)]
+    pub(crate) description: String,
 }
 
 #[derive(serde::Deserialize)]
@@ -80,7 +86,7 @@ pub enum RaidSheetType {
 
 pub struct RaidSheet {
    pub(crate) state: RaidSheetState,
-   pub(crate) ui_sender: Sender<RaidHelperUIStatus>,
+   pub(crate) ui_sender: UnboundedSender<RaidHelperUIStatus>,
    pub(crate) ui_reciever: Receiver<RaidHelperCheckerStatus>,
    pub(crate) search_filter: String,
    pub(crate) question_string: String,
@@ -89,7 +95,19 @@ pub struct RaidSheet {
    // Player stuff
    pub(crate) active_players: Vec<PlayerData>,
    pub(crate) queued_players: Vec<PlayerData>,
-   pub(crate) sheet_type: RaidSheetType
+   pub(crate) sheet_type: RaidSheetType,
+   // Start of the event on the sheet, may belong to a check that's still running rather than last_raid.
+   pub(crate) event_start: Option<i64>,
+   pub(crate) show_needs_attention: bool,
+   needs_attention_names: BTreeMap<String, String>,
+
+   task: Option<JoinHandle<()>>,
+   // Saves the running check's progress, see CheckProgress.
+   progress: ProgressWriter,
+   // Players already done by an interrupted check, reused by the next check of the same event.
+   resumed: Option<CheckProgress>,
+   // Sign-ups imported from a file or pasted list, checked by the next check of their import URL.
+   imported: Option<Vec<Player>>,
 }
 
 impl Default for Player {
@@ -107,7 +125,7 @@ impl Default for Player {
 }
 impl Default for RaidSheet {
     fn default() -> Self {
-        let (tx, _rx) = mpsc::channel();
+        let (tx, _rx) = unbounded_channel();
         let (_tx2, rx2) = mpsc::channel();
         Self {
             state: RaidSheetState::None,
@@ -119,7 +137,15 @@ impl Default for RaidSheet {
 
             active_players: Vec::new(),
             queued_players: Vec::new(),
-            sheet_type: RaidSheetType::Normal
+            sheet_type: RaidSheetType::Normal,
+            event_start: None,
+            show_needs_attention: false,
+            needs_attention_names: BTreeMap::new(),
+
+            task: None,
+            progress: ProgressWriter::spawn(CHECK_PROGRESS_FILE),
+            resumed: None,
+            imported: None,
         }
     }
 }
@@ -140,6 +166,8 @@ pub enum RaidHelperCheckerStatus {
     Search((String, Option<String>, Vec<(String, String)>)),
     Question(String),
     QuestionStringSkip(String),
+    CheckStarted(RaidSheetType, Option<i64>), // Sheet type and event start, sent before the first player
+    PlayerChecked(PlayerData), // A single player of a sign-up check, sent as soon as it's done
     CheckResults(LastRaid),
     PlayerResult(PlayerData),
     PlayerResultSheet(PlayerData, String)
@@ -149,22 +177,117 @@ pub enum RaidHelperCheckerStatus {
 pub enum PlayerOnlyCheckType {
     None,
     Player,
-    PlayerFromSheet(String)
+    PlayerFromSheet(String),
+    ArmoryUrlFromSheet(String), // Discord ID, the check URL is the character's armory URL
 }
 
-fn should_check_player(player: &Player) -> bool {
-    let valid_status = player.status == "primary" || player.status == "queued";
-    let valid_role = match player.roleName.clone().unwrap_or("".to_string()).as_str().to_lowercase().as_str() {
-        "tanks" | "healers" | "ranged" | "melee" => true,
-        _ => false,
-    };
+impl PlayerOnlyCheckType {
+    fn sheet_discord_id(&self) -> Option<String> {
+        match self {
+            PlayerOnlyCheckType::PlayerFromSheet(discord) | PlayerOnlyCheckType::ArmoryUrlFromSheet(discord) => Some(discord.clone()),
+            _ => None
+        }
+    }
+}
+
+// What the user picked for a sign-up in the needs attention panel.
+pub enum NeedsAttentionAction {
+    Resolve(String, String), // Discord ID, armory URL
+    Search(String, String), // Discord ID, name
+}
+
+// Each entry paired with its position in the sign-up list.
+type Positioned<T> = Vec<(usize, T)>;
+
+impl RaidHelper {
+    // An event for sign-ups that didn't come from raid-helper. It has no ID, so there's nothing to fetch or change on raid-helper.
+    fn imported(url: &str, signups: Vec<Player>) -> Self {
+        Self {
+            signUps: signups,
+            name: url.trim_start_matches(IMPORT_URL_PREFIX).to_owned(),
+            id: String::new(),
+            templateId: None,
+            roles: None,
+            start_time: None,
+            description: String::new(),
+        }
+    }
+}
+
+pub(crate) async fn fetch_event(url: &str) -> Result<RaidHelper, String> {
+    let client = http_client::client();
+    let response = client.send(client.get(url)).await.map_err(|err| format!("Failed to get raid: {:?}", err))?;
+    let bytes = response.bytes().await.map_err(|err| format!("Failed to get bytes: {:?}", err))?;
+    serde_json::from_str(&String::from_utf8_lossy(&bytes)).map_err(|err| format!("Failed to parse response: {:?}", err))
+}
+
+// Whether a result still matches the sign-up, anything changed on the sign-up needs a fresh check.
+pub(crate) fn is_same_signup(data: &PlayerData, player: &Player) -> bool {
+    data.discord_id == player.userId
+        && data.name == player.name
+        && data.spec_name == player.specName.clone().unwrap_or_default()
+        && data.class_name == player.className.to_lowercase()
+        && data.status == player.status
+}
+
+// Sign-ups that already have a matching result, from an interrupted check or a recent check of the same event, keep it.
+// The rest still need checking, sign-ups that were withdrawn simply aren't in viable anymore.
+fn split_checked(viable: Vec<Player>, previous: &[PlayerData]) -> (Positioned<PlayerData>, Positioned<Player>) {
+    let mut done = Vec::new();
+    let mut to_check = Vec::new();
+    for (index, player) in viable.into_iter().enumerate() {
+        match previous.iter().find(|x| is_same_signup(x, &player)) {
+            Some(data) => done.push((index, data.clone())),
+            None => to_check.push((index, player)),
+        }
+    }
+    (done, to_check)
+}
+
+// The settings a result depends on besides the sign-up, saved kills and buffs are worked out for the saved raids at check time
+// and the sign-up filter decides whether the player is queued.
+pub(crate) fn check_context(settings: &config::settings::Settings, raid_saved_check: &BTreeMap<i32, RequiredRaid>) -> String {
+    let saved_raids = serde_json::to_string(raid_saved_check).unwrap_or_default();
+    let signup_filter = serde_json::to_string(&settings.current_preset.signup_filter).unwrap_or_default();
+    format!("{:x}", md5::compute(format!("{}|{}|{}|{}", settings.current_preset.name, settings.current_preset.region.slug(), saved_raids, signup_filter)))
+}
 
-    let valid_class = match player.className.to_lowercase().as_str() {
-        "tank" | "healer" | "ranged" | "melee" | "dps" => true,
-        _ => false,
-    };
+// Results from an interrupted check, and ones from the last check of this event until they're too old.
+// Anything that wasn't actually checked, skipped or still needing attention, or was checked against other settings is always retried.
+fn reusable_results(resumed: Vec<PlayerData>, last_players: &[PlayerData], context: &str, max_age: i64, now: i64) -> Vec<PlayerData> {
+    resumed.into_iter()
+        .chain(last_players.iter().filter(|x| now - x.checked_at < max_age).cloned())
+        .filter(|x| x.was_checked() && x.checked_for == context)
+        .collect()
+}
 
-    valid_status && (valid_class || valid_role) && player.className.to_lowercase() != "tentative"
+// Rechecking a finished import has only the results to go on, everyone on the list was checked.
+fn import_signups(url: &str, imported: Option<Vec<Player>>, resumed: &[PlayerData], last_raid: &LastRaid) -> Vec<Player> {
+    imported.unwrap_or_else(|| {
+        let last_players = if last_raid.raid_url == url { &last_raid.players[..] } else { &[] };
+        let mut players = resumed.to_vec();
+        players.extend(last_players.iter().filter(|x| !resumed.iter().any(|y| y.discord_id == x.discord_id)).cloned());
+        signups_from_results(&players)
+    })
+}
+
+// The sign-up a player from the sheet was checked as, so a recheck sees the same role, class and spec.
+fn sheet_signup(name: &str, discord: &str, players: &[PlayerData]) -> Player {
+    let mut player = Player { name: name.to_owned(), ..Player::default() };
+    if let Some(p) = players.iter().find(|x| x.discord_id == discord) {
+        player.className = p.class_name.clone();
+        player.roleName = Some(p.role_name.clone());
+        player.specName = Some(p.spec_name.clone());
+        player.userId = p.discord_id.clone();
+        player.status = if !p.status.is_empty() {
+            p.status.clone()
+        } else if p.queued {
+            "queued".to_string()
+        } else {
+            "primary".to_string()
+        };
+    }
+    player
 }
 
 impl RaidSheet {
@@ -173,6 +296,7 @@ impl RaidSheet {
         self.active_players.clear();
         self.queued_players.clear();
         self.sheet_type = last_raid.sheet_type.clone();
+        self.event_start = last_raid.start_time;
         for player in last_raid.players.iter() {
             if player.queued == false {
                 self.active_players.push(player.clone());
@@ -186,38 +310,50 @@ impl RaidSheet {
     pub fn init(&mut self, url: String, is_player_only: PlayerOnlyCheckType, settings: config::settings::Settings, expansions: config::expansion_config::ExpansionsConfig, realms: config::realms::RealmJson,
         raid_saved_check: BTreeMap<i32, RequiredRaid>, mut last_raid: LastRaid)
     {
-        let (uis, thread_reciever) = mpsc::channel();
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        let (resumed, imported) = self.take_pending(&url);
+        let progress = self.progress.for_new_check();
+        self.progress = progress.clone();
+
+        let (uis, thread_reciever) = unbounded_channel();
         let (thread_sender, uir) = mpsc::channel();
         self.ui_sender = uis;
         self.ui_reciever = uir;
         self.state = RaidSheetState::Init;
 
         let thread_receiver = Arc::new(Mutex::new(thread_reciever));
+        // Pick up any network settings changed since the last check.
+        http_client::configure(&settings.http);
+        // The per-player recheck wants what's on the armory right now, not what was cached a few minutes ago.
+        let force_refresh = matches!(is_player_only, PlayerOnlyCheckType::PlayerFromSheet(_));
+        let source = character_source::from_settings(&settings, &expansions, force_refresh);
+        let checked_for = check_context(&settings, &raid_saved_check);
 
         if is_player_only != PlayerOnlyCheckType::None {
-            thread::spawn(move || {
+            self.task = Some(http_client::runtime().spawn(async move {
                 let _ = thread_sender.send(RaidHelperCheckerStatus::Checking(format!("player {}", url.clone())));
                 let mut player: Player = Player::default();
                 player.name = url.clone();
 
                 if is_player_only != PlayerOnlyCheckType::Player { // from sheet
                     info!("Looking for player {} in last raid data", url.clone());
-                    if let PlayerOnlyCheckType::PlayerFromSheet(discord) = is_player_only.clone() {
-                        for p in last_raid.players.iter() {
-                            if p.discord_id == discord {
-                                player.className = p.class_name.clone();
-                                player.roleName = Some(p.role_name.clone());
-                                player.userId = p.discord_id.clone();
-                                player.status = if p.queued { "queued".to_string() } else { "primary".to_string() };
-                                break;
-                            }
-                        }
+                    if let Some(discord) = is_player_only.sheet_discord_id() {
+                        player = sheet_signup(&url, &discord, &last_raid.players);
                     }
                 }
 
-                let player_data = PlayerChecker::check_player(&player, &thread_sender, &thread_receiver, &settings, &expansions, &realms, &raid_saved_check, None);
+                let char_url = match is_player_only {
+                    PlayerOnlyCheckType::ArmoryUrlFromSheet(_) => Some(url.clone()),
+                    _ => None
+                };
+                let mut player_data = PlayerChecker::check_player(&player, &thread_sender, Some(&thread_receiver), &settings, &expansions, &realms, &raid_saved_check, char_url, &*source).await;
+                if let Some(player) = player_data.as_mut() {
+                    player.checked_for = checked_for;
+                }
                 if player_data.is_some() {
-                    if let PlayerOnlyCheckType::PlayerFromSheet(data) = is_player_only {
+                    if let Some(data) = is_player_only.sheet_discord_id() {
                         let _ = thread_sender.send(RaidHelperCheckerStatus::PlayerResultSheet(player_data.unwrap(), data));                        
                     } else {
                         let _ = thread_sender.send(RaidHelperCheckerStatus::PlayerResult(player_data.unwrap()));
@@ -225,61 +361,67 @@ impl RaidSheet {
                 } else {
                     let _ = thread_sender.send(RaidHelperCheckerStatus::Error(format!("Could not find player {:?}", url.clone())));
                 }
-            });
+            }));
             return;
         }
 
 
         
 
-        thread::spawn(move || {
-            let client = Client::builder()
-                .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36")
-                .build().unwrap();
-
-            let mut response = client
-                .get(url.clone())
-                .send();
-
-            let o_bytes = response.unwrap().bytes();
-            if o_bytes.is_err() {
-                let _ = thread_sender.send(RaidHelperCheckerStatus::Error(format!("Failed to get bytes: {:?}", o_bytes.err())));
-                return;
-            }
-            let bytes = o_bytes.unwrap();
-
-
-            let raid_response_res: Result<RaidHelper, serde_json::Error> = serde_json::from_str(&String::from_utf8_lossy(&bytes));
-            if raid_response_res.is_err() {
-                let _ = thread_sender.send(RaidHelperCheckerStatus::Error(format!("Failed to parse response: {:?}", raid_response_res.err())));
-                return;
-            }
-
-            let raid_response = raid_response_res.unwrap();
+        self.task = Some(http_client::runtime().spawn(async move {
+            let client = http_client::client();
+            let raid_response = if is_import_url(&url) {
+                RaidHelper::imported(&url, import_signups(&url, imported, &resumed, &last_raid))
+            } else {
+                match fetch_event(&url).await {
+                    Ok(raid_response) => raid_response,
+                    Err(err) => {
+                        let _ = thread_sender.send(RaidHelperCheckerStatus::Error(err));
+                        return;
+                    }
+                }
+            };
 
             if last_raid.raid_url != url {
                 last_raid.raid_url = String::new();
                 last_raid.players.clear();
             }
 
-            let mut viable: Vec<&Player> = raid_response.signUps.iter().filter(|x| should_check_player(x)).collect();
+            let mut viable: Vec<&Player> = raid_response.signUps.iter().filter(|x| settings.current_preset.signup_filter.should_check(x)).collect();
             viable.sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap());
 
-            
-            let total_players = viable.len();
-            
-            let pool = ThreadPoolBuilder::new()
-                .num_threads(3)
-                .build()
-                .expect("Failed to build thread pool");
+            let previous = reusable_results(resumed, &last_raid.players, &checked_for, settings.recheck_after_minutes * 60, Utc::now().timestamp());
 
-            let count = Arc::new(AtomicUsize::new(0));
+            let (mut done, to_check) = split_checked(viable.into_iter().cloned().collect(), &previous);
+            if !done.is_empty() {
+                info!("{} of {} sign-ups of {} are unchanged, checking {}", done.len(), done.len() + to_check.len(), raid_response.name, to_check.len());
+            }
+            
+            let total_players = done.len() + to_check.len();
+            let count = AtomicUsize::new(done.len());
+
+            // Read on every check so edits to the file are picked up without restarting.
+            let templates = TemplatesConfig::read_or_create(TEMPLATES_FILE).unwrap_or_default();
+            let template = templates.for_template(raid_response.templateId.as_deref());
+            let sheet_type = template.sheet_type.clone();
+            let _ = thread_sender.send(RaidHelperCheckerStatus::CheckStarted(sheet_type.clone(), raid_response.start_time));
+            for (_, player) in done.iter_mut() {
+                template.apply(player);
+                let _ = thread_sender.send(RaidHelperCheckerStatus::PlayerChecked(player.clone()));
+            }
 
+            progress.started(CheckProgress {
+                imported: is_import_url(&url).then(|| raid_response.signUps.clone()),
+                ..CheckProgress::new(url.clone(), raid_response.name.clone(), done.iter().map(|x| x.1.clone()).collect())
+            });
 
-            let mut players_data: Vec<PlayerData> = pool.install(|| {
-                viable
-                    .par_iter()
-                    .map(|player| {
+            // Players are sent to the UI as soon as they're done, a slow or stuck one doesn't hold the others back.
+            // The final list is put back into sign-up order once everyone is in.
+            let mut players_data: Positioned<PlayerData> = stream::iter(to_check)
+                .map(|(index, player)| {
+                    let (thread_sender, settings, expansions, realms, raid_saved_check, last_raid, source, count, progress, template, checked_for) =
+                        (&thread_sender, &settings, &expansions, &realms, &raid_saved_check, &last_raid, &source, &count, &progress, template, &checked_for);
+                    async move {
                         let current_count = count.fetch_add(1, Ordering::SeqCst) + 1;
                         
                         let _ = thread_sender.send(RaidHelperCheckerStatus::Checking(format!(
@@ -295,16 +437,18 @@ impl RaidSheet {
                             None
                         };
             
-                        let ret = PlayerChecker::check_player(
-                            player, 
-                            &thread_sender, 
-                            &thread_receiver, 
-                            &settings, 
-                            &expansions, 
-                            &realms, 
-                            &raid_saved_check, 
-                            player_url
-                        ).unwrap_or(PlayerData {
+                        // No receiver, anyone that can't be found on their own is left for the needs attention panel.
+                        let mut ret = PlayerChecker::check_player(
+                            &player, 
+                            thread_sender, 
+                            None, 
+                            settings, 
+                            expansions, 
+                            realms, 
+                            raid_saved_check, 
+                            player_url,
+                            &**source
+                        ).await.unwrap_or(PlayerData {
                             discord_id: player.userId.clone(),
                             name: player.name.clone(),
                             status: player.status.clone(),
@@ -322,31 +466,44 @@ impl RaidSheet {
                             pvp_gear: false,
                             skip_reason: Some("Could not find player".to_owned()),
                             armory_url: "".to_owned(),
-                            queued: player.status.to_lowercase() != "primary" || player.className.to_lowercase() == "bench" ,
+                            queued: settings.current_preset.signup_filter.is_queued(&player),
                             confirmed: 0,
                             class_name: player.className.clone().to_lowercase(),
                             role_name: player.roleName.clone().unwrap_or("".to_string()).to_lowercase(),
-                            dirty_state: -1
+                            wow_class: String::new(),
+                            dirty_state: -1,
+                            needs_attention: None,
+                            spec_name: player.specName.clone().unwrap_or_default(),
+                            checked_at: Utc::now().timestamp(),
+                            checked_for: String::new(),
+                            issues: Vec::new(),
+                            readiness: None,
                         });
+                        template.apply(&mut ret);
+                        ret.checked_for = checked_for.clone();
 
-                        ret
-                    })
-                    .collect()
-            });
-
-            let sheet_type = if raid_response.templateId.unwrap_or("N/A".to_string()) == "wowretail1" {
-                RaidSheetType::Classes
+                        progress.checked(ret.clone());
+                        let _ = thread_sender.send(RaidHelperCheckerStatus::PlayerChecked(ret.clone()));
+                        (index, ret)
+                    }
+                })
+                .buffer_unordered(settings.check_concurrency.max(1))
+                .collect()
+                .await;
+
+            players_data.extend(done);
+            players_data.sort_by_key(|x| x.0);
+            let mut players_data: Vec<PlayerData> = players_data.into_iter().map(|x| x.1).collect();
+
+            // Imports have no raid plan.
+            let response = if raid_response.id.is_empty() {
+                Err(())
             } else {
-                RaidSheetType::Normal
+                client.send(client.get(format!("https://raid-helper.xyz/api/raidplan/{}", raid_response.id))).await.map_err(|_| ())
             };
 
-            response = client
-                .get(format!("https://raid-helper.xyz/api/raidplan/{}", raid_response.id))
-                .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36")
-                .send();
-
             if !response.is_err() {
-                let raid_plan: Result<RaidHelperRaidPlan, serde_json::Error>  = serde_json::from_str(&response.unwrap().text().unwrap());
+                let raid_plan: Result<RaidHelperRaidPlan, serde_json::Error>  = serde_json::from_str(&response.unwrap().text().await.unwrap());
                 if raid_plan.is_ok() {
                     let raid_plan = raid_plan.unwrap();
                     for slot in raid_plan.slots.iter() {
@@ -367,7 +524,9 @@ impl RaidSheet {
             last_raid.raid_url = url.clone();
             last_raid.raid_name = raid_response.name.clone();
             last_raid.raid_id = raid_response.id.clone();
+            last_raid.start_time = raid_response.start_time;
             last_raid.save();
+            progress.clear();
 
             let _ = thread_sender.send(RaidHelperCheckerStatus::CheckResults(LastRaid {
                 raid_url: url,
@@ -375,8 +534,151 @@ impl RaidSheet {
                 raid_id: raid_response.id,
                 sheet_type: sheet_type,
                 players: players_data,
+                start_time: raid_response.start_time,
             }));
-       });
+       }));
+    }
+
+    // The next check of the progress' event only checks the sign-ups that weren't done yet. Returns the URL to check.
+    pub fn resume(&mut self, progress: CheckProgress) -> String {
+        let url = progress.raid_url.clone();
+        self.resumed = Some(progress);
+        url
+    }
+
+    // The interrupted check's results and the sign-ups to import, if they're for this URL. A resumed import brings its own sign-ups.
+    fn take_pending(&mut self, url: &str) -> (Vec<PlayerData>, Option<Vec<Player>>) {
+        let resumed = self.resumed.take().filter(|x| x.raid_url == url);
+        let imported = self.imported.take().or_else(|| resumed.as_ref().and_then(|x| x.imported.clone()));
+        (resumed.map(|x| x.players).unwrap_or_default(), imported)
+    }
+
+    // Imported sign-ups are checked by the next check of the returned URL.
+    pub fn import(&mut self, name: &str, signups: Vec<Player>) -> String {
+        self.imported = Some(signups);
+        import_url(name)
+    }
+
+    pub fn needs_attention_count(&self) -> usize {
+        self.active_players.iter().chain(self.queued_players.iter()).filter(|x| x.needs_attention.is_some()).count()
+    }
+
+    fn skip_needs_attention(&mut self, discord_id: &str, last_raid: &mut LastRaid) {
+        for player in self.active_players.iter_mut().chain(self.queued_players.iter_mut()).chain(last_raid.players.iter_mut()) {
+            if player.discord_id == discord_id {
+                player.needs_attention = None;
+                player.skip_reason = Some("Skipped by user.".to_owned());
+            }
+        }
+        self.dirty = true;
+        last_raid.save();
+    }
+
+    // Sign-ups the check couldn't match to a character on its own. Picking a candidate or searching a name checks just that player.
+    pub fn draw_needs_attention(&mut self, ctx: &egui::Context, last_raid: &mut LastRaid) -> Option<NeedsAttentionAction> {
+        let mut action = None;
+        let mut skipped: Option<String> = None;
+        let mut open = self.show_needs_attention;
+
+        egui::Window::new(format!("Needs attention ({})", self.needs_attention_count()))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("These sign-ups couldn't be matched to a character automatically.");
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for player in self.active_players.iter().chain(self.queued_players.iter()) {
+                        let Some(needs_attention) = &player.needs_attention else {
+                            continue;
+                        };
+
+                        ui.separator();
+                        ui.label(egui::RichText::new(&player.name).strong());
+                        ui.label(&needs_attention.reason);
+                        for (label, url) in needs_attention.candidates.iter() {
+                            if ui.button(label).clicked() {
+                                action = Some(NeedsAttentionAction::Resolve(player.discord_id.clone(), url.clone()));
+                            }
+                        }
+
+                        ui.horizontal(|ui| {
+                            let name = self.needs_attention_names.entry(player.discord_id.clone()).or_default();
+                            ui.add(egui::TextEdit::singleline(name).hint_text("Name or Name-Realm").desired_width(150.0));
+                            if ui.button("Search").clicked() && !name.is_empty() {
+                                action = Some(NeedsAttentionAction::Search(player.discord_id.clone(), name.clone()));
+                            }
+                            if ui.button("Skip").clicked() {
+                                skipped = Some(player.discord_id.clone());
+                            }
+                        });
+                    }
+                });
+            });
+
+        if let Some(discord_id) = skipped {
+            self.skip_needs_attention(&discord_id, last_raid);
+        }
+        self.show_needs_attention = open && self.needs_attention_count() > 0;
+        action
+    }
+
+    fn insert_player(&mut self, player: PlayerData) {
+        let players = if player.queued { &mut self.queued_players } else { &mut self.active_players };
+        if let Some(index) = players.iter().position(|x| x.discord_id == player.discord_id) {
+            players[index] = player;
+        } else {
+            players.push(player);
+        }
+        self.dirty = true;
+    }
+
+    // Swaps a player rechecked from the sheet into the last raid and the sheet.
+    fn apply_sheet_recheck(&mut self, mut player: PlayerData, discord: String, last_raid: &mut LastRaid) {
+        info!("Updating player data from sheet recheck: {}", player.name);
+        player.discord_id = discord;
+        let Some(old_player) = last_raid.players.iter_mut().find(|x| x.discord_id == player.discord_id) else {
+            return;
+        };
+        info!("Found old player data for {}", old_player.name);
+        player.name = old_player.name.clone();
+        player.queued = old_player.queued;
+        // Only the character was rechecked, the sign-up itself and its template mapping still hold.
+        player.class_name = old_player.class_name.clone();
+        player.role_name = old_player.role_name.clone();
+        player.wow_class = old_player.wow_class.clone();
+        player.spec_name = old_player.spec_name.clone();
+        player.status = old_player.status.clone();
+        *old_player = player.clone();
+        self.insert_player(player);
+    }
+
+    // Stops the running check, dropping its in-flight requests and anything not started yet. last_raid.json is only written
+    // when a check finishes, so it's left as it was. keep_results leaves the players checked so far on the sheet, otherwise it goes back to the last raid.
+    pub fn cancel(&mut self, last_raid: &LastRaid, keep_results: bool) {
+        if let Some(task) = self.task.take() {
+            info!("Cancelling the running check (keep results: {})", keep_results);
+            task.abort();
+            self.progress.clear();
+        }
+
+        if !keep_results {
+            self.init_from_last_raid(last_raid);
+            return;
+        }
+
+        // Players that finished right before the abort may still be waiting in the channel.
+        while let Ok(message) = self.ui_reciever.try_recv() {
+            match message {
+                RaidHelperCheckerStatus::CheckStarted(sheet_type, start_time) => {
+                    self.active_players.clear();
+                    self.queued_players.clear();
+                    self.sheet_type = sheet_type;
+                    self.event_start = start_time;
+                },
+                RaidHelperCheckerStatus::PlayerChecked(player) => self.insert_player(player),
+                _ => {}
+            }
+        }
+        self.state = RaidSheetState::None;
     }
 
     pub fn recheck_raid_plan(&mut self, last_raid: &mut LastRaid) {
@@ -384,14 +686,13 @@ impl RaidSheet {
             return;
         }
         
-        let client = Client::new();
-        let response = client
-            .get(format!("https://raid-helper.xyz/api/raidplan/{}", last_raid.raid_id))
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36")
-            .send();
+        let client = http_client::client();
+        let response = http_client::block_on(async {
+            client.send(client.get(format!("https://raid-helper.xyz/api/raidplan/{}", last_raid.raid_id))).await?.text().await
+        });
 
         if !response.is_err() {
-            let raid_plan: Result<RaidHelperRaidPlan, serde_json::Error>  = serde_json::from_str(&response.unwrap().text().unwrap());
+            let raid_plan: Result<RaidHelperRaidPlan, serde_json::Error>  = serde_json::from_str(&response.unwrap());
             self.active_players.iter_mut().for_each(|x| x.confirmed = RAID_PLAN_NONE);
             self.queued_players.iter_mut().for_each(|x| x.confirmed = RAID_PLAN_NONE);
 
@@ -468,6 +769,18 @@ impl RaidSheet {
                     self.question_string = String::default();
                 },
 
+                RaidHelperCheckerStatus::CheckStarted(sheet_type, start_time) => {
+                    self.active_players.clear();
+                    self.queued_players.clear();
+                    self.sheet_type = sheet_type;
+                    self.event_start = start_time;
+                    *just_checked = true;
+                }
+
+                RaidHelperCheckerStatus::PlayerChecked(player) => {
+                    self.insert_player(player);
+                }
+
                 RaidHelperCheckerStatus::CheckResults(results) => {
                     self.active_players.clear();
                     self.queued_players.clear();
@@ -483,6 +796,9 @@ impl RaidSheet {
                     self.dirty = true;
                     *just_checked = true;
                     self.sheet_type = results.sheet_type;
+                    self.event_start = results.start_time;
+                    self.task = None;
+                    self.show_needs_attention = self.needs_attention_count() > 0;
                     self.state = RaidSheetState::None;
                 }
 
@@ -491,33 +807,8 @@ impl RaidSheet {
                     self.state = RaidSheetState::None;
                 }
 
-                RaidHelperCheckerStatus::PlayerResultSheet(mut player, discord) => {
-                    info!("Updating player data from sheet recheck: {}", player.name);
-                    player.discord_id = discord.clone();
-                    let old_player_data = last_raid.players.iter_mut().find(|x| x.discord_id == player.discord_id);
-                    if let Some(old_player) = old_player_data {
-                        player.name = old_player.name.clone();
-                        info!("Found old player data for {}", player.name);
-                        let queued = old_player.queued;
-                        player.queued = queued;
-                        *old_player = player.clone();
-                        if queued {
-                            info!("Updating queued player data for {}", player.name);
-                            
-                            if let Some(index) = self.queued_players.iter().position(|x| x.discord_id == player.discord_id) {
-                                self.queued_players[index] = player;
-                            } else {
-                                self.queued_players.push(player);
-                            }
-                        } else {
-                            info!("Updating active player data for {}", player.name);
-                            if let Some(index) = self.active_players.iter().position(|x| x.discord_id == player.discord_id) {
-                                self.active_players[index] = player;
-                            } else {
-                                self.active_players.push(player);
-                            }
-                        }
-                    }
+                RaidHelperCheckerStatus::PlayerResultSheet(player, discord) => {
+                    self.apply_sheet_recheck(player, discord, last_raid);
                     *just_checked = true;
                     self.dirty = true;
                     last_raid.save();
@@ -527,6 +818,7 @@ impl RaidSheet {
         }
 
         let mut wait = false;
+        let mut cancel: Option<bool> = None;
         match &self.state {
             RaidSheetState::Init => {
                 egui::Window::new("Raid Helper - Getting Raid Helper Data")
@@ -537,6 +829,9 @@ impl RaidSheet {
                             ui.spinner();
                             ui.label("Getting started");
                         });
+                        if ui.button("Cancel").clicked() {
+                            cancel = Some(false);
+                        }
                     });
             },
 
@@ -552,6 +847,14 @@ impl RaidSheet {
                                 _ => "No data yet.".to_owned()
                             });
                         });
+                        ui.horizontal(|ui| {
+                            if ui.button("Cancel").on_hover_text("Stops the check and goes back to the last finished raid.").clicked() {
+                                cancel = Some(false);
+                            }
+                            if ui.button("Cancel, keep results").on_hover_text("Stops the check but keeps the players checked so far on the sheet. They aren't saved to the last raid.").clicked() {
+                                cancel = Some(true);
+                            }
+                        });
                     });
             },
             
@@ -687,5 +990,183 @@ impl RaidSheet {
         if wait == true {
             self.state = RaidSheetState::Wait;
         }
+
+        if let Some(keep_results) = cancel {
+            self.cancel(last_raid, keep_results);
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::checker::check_player::NeedsAttention;
+
+    fn player(discord_id: &str, queued: bool) -> PlayerData {
+        PlayerData { queued, ..PlayerData::for_test(discord_id) }
+    }
+
+    // A sheet with a check that never finishes, fed from the returned sender.
+    fn running_sheet() -> (RaidSheet, mpsc::Sender<RaidHelperCheckerStatus>) {
+        let (sender, receiver) = mpsc::channel();
+        let sheet = RaidSheet {
+            ui_reciever: receiver,
+            state: RaidSheetState::Checking(String::new()),
+            task: Some(http_client::runtime().spawn(async { tokio::time::sleep(Duration::from_secs(3600)).await })),
+            ..RaidSheet::default()
+        };
+        (sheet, sender)
+    }
+
+    fn last_raid() -> LastRaid {
+        LastRaid {
+            players: vec![player("old", false)],
+            start_time: Some(1),
+            ..LastRaid::default()
+        }
+    }
+
+    #[test]
+    fn cancel_keeps_streamed_players() {
+        let (mut sheet, sender) = running_sheet();
+        sender.send(RaidHelperCheckerStatus::CheckStarted(RaidSheetType::Classes, Some(2))).unwrap();
+        sender.send(RaidHelperCheckerStatus::PlayerChecked(player("active", false))).unwrap();
+        sender.send(RaidHelperCheckerStatus::PlayerChecked(player("queued", true))).unwrap();
+
+        sheet.cancel(&last_raid(), true);
+        assert!(sheet.task.is_none());
+        assert!(sheet.state == RaidSheetState::None);
+        assert_eq!(sheet.active_players.iter().map(|x| x.discord_id.as_str()).collect::<Vec<_>>(), ["active"]);
+        assert_eq!(sheet.queued_players.iter().map(|x| x.discord_id.as_str()).collect::<Vec<_>>(), ["queued"]);
+        assert_eq!(sheet.sheet_type, RaidSheetType::Classes);
+        assert_eq!(sheet.event_start, Some(2));
+    }
+
+    #[test]
+    fn cancel_without_results_restores_last_raid() {
+        let (mut sheet, sender) = running_sheet();
+        let task = sheet.task.as_ref().unwrap().abort_handle();
+        sender.send(RaidHelperCheckerStatus::PlayerChecked(player("active", false))).unwrap();
+        sheet.insert_player(player("streamed", false));
+
+        sheet.cancel(&last_raid(), false);
+        http_client::block_on(async { tokio::time::sleep(Duration::from_millis(10)).await });
+        assert!(task.is_finished());
+        assert!(sheet.state == RaidSheetState::None);
+        assert_eq!(sheet.active_players.iter().map(|x| x.discord_id.as_str()).collect::<Vec<_>>(), ["old"]);
+        assert_eq!(sheet.event_start, Some(1));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn only_new_and_changed_signups_are_checked() {
+        let signup = |id: &str| Player {
+            name: id.to_owned(),
+            userId: id.to_owned(),
+            specName: Some("Fury".to_owned()),
+            className: "DPS".to_owned(),
+            status: "primary".to_owned(),
+            ..Player::default()
+        };
+        let stored = |id: &str| PlayerData {
+            spec_name: "Fury".to_owned(),
+            class_name: "dps".to_owned(),
+            status: "primary".to_owned(),
+            ..player(id, false)
+        };
+        let renamed = PlayerData { name: "old name".to_owned(), ..stored("c") };
+        let respecced = PlayerData { spec_name: "Arms".to_owned(), ..stored("d") };
+
+        let (done, to_check) = split_checked(vec![signup("a"), signup("b"), signup("c"), signup("d")], &[stored("b"), renamed, respecced, stored("withdrawn")]);
+        assert_eq!(done.iter().map(|x| (x.0, x.1.discord_id.as_str())).collect::<Vec<_>>(), [(1, "b")]);
+        assert_eq!(to_check.iter().map(|x| (x.0, x.1.userId.as_str())).collect::<Vec<_>>(), [(0, "a"), (2, "c"), (3, "d")]);
+    }
+
+    #[test]
+    fn unchecked_results_are_not_reused() {
+        let checked = |id: &str, checked_at: i64| PlayerData { checked_at, ..player(id, false) };
+        let skipped = PlayerData { skip_reason: Some("Skipped by user.".to_owned()), ..checked("skipped", 100) };
+        let attention = PlayerData { needs_attention: Some(NeedsAttention::default()), ..checked("attention", 100) };
+        let interrupted = PlayerData { skip_reason: Some("Could not find player".to_owned()), ..checked("missing", 0) };
+
+        let previous = reusable_results(vec![checked("resumed", 0), interrupted], &[checked("fresh", 90), checked("old", 10), skipped, attention], "", 30, 100);
+        assert_eq!(previous.iter().map(|x| x.discord_id.as_str()).collect::<Vec<_>>(), ["resumed", "fresh"]);
+    }
+
+    #[test]
+    fn results_for_other_settings_are_not_reused() {
+        let mut settings = config::settings::Settings::default();
+        let saved_raids = BTreeMap::new();
+        let context = check_context(&settings, &saved_raids);
+        let player = |id: &str, checked_for: &str| PlayerData { checked_at: 100, checked_for: checked_for.to_owned(), ..player(id, false) };
+
+        settings.current_preset.region = config::region::Region::US;
+        let other_region = check_context(&settings, &saved_raids);
+        assert_ne!(context, other_region);
+
+        // Queueing tentative sign-ups changes who ends up on the bench.
+        let mut filtered = config::settings::Settings::default();
+        filtered.current_preset.signup_filter.statuses.insert("tentative".to_owned(), config::signup_filter::SignupHandling::Queued);
+        assert_ne!(context, check_context(&filtered, &saved_raids));
+
+        let previous = reusable_results(Vec::new(), &[player("same", &context), player("region", &other_region), player("old save", "")], &context, 30, 100);
+        assert_eq!(previous.iter().map(|x| x.discord_id.as_str()).collect::<Vec<_>>(), ["same"]);
+    }
+
+    #[test]
+    fn sheet_recheck_keeps_spec_overrides() {
+        let mut preset = config::settings::Preset { average_ilvl: 640, ..config::settings::Preset::default() };
+        let mut holy = config::requirements::RequirementOverride::new(config::requirements::OverrideScope::Spec, "Holy");
+        holy.average_ilvl = Some(630);
+        preset.overrides = vec![holy];
+        let healer = PlayerData {
+            spec_name: "Holy".to_owned(),
+            class_name: "healer".to_owned(),
+            role_name: "healer".to_owned(),
+            wow_class: "Priest".to_owned(),
+            status: "primary".to_owned(),
+            ..player("healer", false)
+        };
+        let mut last_raid = LastRaid { players: vec![healer.clone()], ..LastRaid::default() };
+
+        let signup = sheet_signup("healer", "healer", &last_raid.players);
+        assert_eq!(signup.specName.as_deref(), Some("Holy"));
+        assert!(is_same_signup(&healer, &signup));
+
+        // The armory knows nothing about the sign-up, the rechecked result only has the character.
+        let rechecked = PlayerData { spec_name: "N/A".to_owned(), status: String::new(), ..PlayerData::for_test("") };
+        let mut sheet = RaidSheet::default();
+        sheet.apply_sheet_recheck(rechecked, "healer".to_owned(), &mut last_raid);
+        assert_eq!(sheet.active_players[0].requirements(&preset).average_ilvl, 630);
+        assert_eq!(last_raid.players[0].requirements(&preset).average_ilvl, 630);
+        assert!(is_same_signup(&last_raid.players[0], &signup));
+    }
+
+    #[test]
+    fn resumed_import_checks_every_signup() {
+        let signups = crate::checker::signup_import::parse_signups("Thrall\nJaina\nAnduin").unwrap();
+        let url = import_url("Imported");
+        let checked = PlayerData {
+            name: "Thrall".to_owned(),
+            class_name: "dps".to_owned(),
+            status: "primary".to_owned(),
+            ..player(&signups[0].userId, false)
+        };
+
+        // Closed after the first sign-up was checked.
+        let path = std::env::temp_dir().join(format!("raidchecker_progress_import_{}.json", std::process::id()));
+        CheckProgress {
+            imported: Some(signups.clone()),
+            ..CheckProgress::new(url.clone(), "Imported".to_owned(), vec![checked])
+        }.save(&path);
+        let progress = CheckProgress::read(&path).unwrap();
+        CheckProgress::clear(&path);
+
+        let mut sheet = RaidSheet::default();
+        assert_eq!(sheet.resume(progress), url);
+        let (resumed, imported) = sheet.take_pending(&url);
+        let (done, to_check) = split_checked(import_signups(&url, imported, &resumed, &LastRaid::default()), &resumed);
+        assert_eq!(done.iter().map(|x| x.1.name.as_str()).collect::<Vec<_>>(), ["Thrall"]);
+        assert_eq!(to_check.iter().map(|x| x.1.name.as_str()).collect::<Vec<_>>(), ["Jaina", "Anduin"]);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{checker::{check_player::PlayerData, gear_checker::GearChecker, rule_checker::RuleChecker}, config::{expansion_config::ExpansionsConfig, settings::{Settings, Slots}}};
+
+// Item levels below the requirement at which the item level part of the score reaches zero.
+const ITEM_LEVEL_RANGE: f32 = 10.0;
+
+pub struct ReadinessChecker;
+
+// A weighted 0-100 score of how ready a player is, with the parts it was made from (each 0-1).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Readiness {
+    pub score: f32,
+    pub parts: Vec<(&'static str, f32)>,
+}
+
+impl Readiness {
+    pub fn describe(&self) -> String {
+        self.parts.iter().map(|x| format!("{}: {:.0}%", x.0, x.1 * 100.0)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+// Raid-wide averages of the checked players, shares are 0-1.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RaidReadiness {
+    pub checked: usize,
+    pub average_score: f32,
+    pub average_ilvl: f32,
+    pub fully_enchanted: f32,
+    pub sockets_filled: f32,
+    pub special_items: f32,
+    pub four_piece: f32,
+    pub embellished: f32,
+    pub all_required_kills: f32,
+}
+
+impl ReadinessChecker {
+    // None for players that weren't checked, or if every weight is 0. Parts the armory didn't tell are left out.
+    pub fn check(settings: &Settings, expansions: &ExpansionsConfig, player: &PlayerData) -> Option<Readiness> {
+        if player.skip_reason.is_some() || player.character.gear.is_empty() {
+            return None;
+        }
+
+        let requirements = player.requirements(&settings.current_preset);
+        let weights = &settings.current_preset.readiness_weights;
+        let mut parts: Vec<(&'static str, f32, f32)> = Vec::new(); // Name, weight, part
+
+        let ilvl = if requirements.average_ilvl <= 0 {
+            1.0
+        } else {
+            1.0 - (requirements.average_ilvl - player.ilvl) as f32 / ITEM_LEVEL_RANGE
+        };
+        parts.push(("Item level", weights.item_level, ilvl));
+
+        let required_gear = Self::required_gear_checks(requirements.slots, player, expansions);
+        if required_gear > 0 {
+            let gear_issues = player.bad_gear.len() + player.bad_socket.len() + player.bad_special_item.len();
+            parts.push(("Gear", weights.gear, 1.0 - gear_issues as f32 / required_gear as f32));
+        }
+
+        if player.tier_count != -1 {
+            parts.push(("Tier", weights.tier, player.tier_count as f32 / 4.0));
+        }
+
+        if player.num_embelishments != -1 {
+            let embellishments = if requirements.embelishments <= 0 {
+                1.0
+            } else {
+                player.num_embelishments as f32 / requirements.embelishments as f32
+            };
+            parts.push(("Embellishments", weights.embellishments, embellishments));
+        }
+
+        let (killed, required) = RuleChecker::required_kills(settings, player);
+        if required > 0 {
+            parts.push(("Required kills", weights.kills, killed as f32 / required as f32));
+        }
+
+        // Halves with each missing buff level.
+        let missing_buffs: i32 = player.buff_status.values().map(|x| x.1.max(0)).sum();
+        parts.push(("Raid buff", weights.buffs, 1.0 / (1.0 + missing_buffs as f32)));
+
+        let parts: Vec<(&'static str, f32, f32)> = parts.into_iter().filter(|x| x.1 > 0.0).map(|x| (x.0, x.1, x.2.clamp(0.0, 1.0))).collect();
+        let total_weight: f32 = parts.iter().map(|x| x.1).sum();
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        Some(Readiness {
+            score: parts.iter().map(|x| x.1 * x.2).sum::<f32>() / total_weight * 100.0,
+            parts: parts.iter().map(|x| (x.0, x.2)).collect(),
+        })
+    }
+
+    // The enchants, sockets and special items the slot settings require on the equipped gear, the slots found the way the gear check finds them.
+    fn required_gear_checks(slots: &Slots, player: &PlayerData, expansions: &ExpansionsConfig) -> usize {
+        let Some(expansion) = expansions.latest_expansion.as_ref() else {
+            return 0;
+        };
+        player.character.gear.iter().filter_map(|(equipped_slot, gear)| {
+            let setting = GearChecker::slot_setting(slots, GearChecker::slot_data(expansion, gear)?)?;
+            let enchant = setting.require_slot && GearChecker::checks_enchant(equipped_slot, gear);
+            Some(enchant as usize + (setting.require_sockets > 0) as usize + setting.require_special_item as usize)
+        }).sum()
+    }
+
+    pub fn summarise(settings: &Settings, expansions: &ExpansionsConfig, players: &[&PlayerData]) -> RaidReadiness {
+        let checked: Vec<&&PlayerData> = players.iter().filter(|x| x.skip_reason.is_none() && !x.character.gear.is_empty()).collect();
+        if checked.is_empty() {
+            return RaidReadiness::default();
+        }
+
+        let count = checked.len() as f32;
+        let share = |f: &dyn Fn(&PlayerData) -> bool| checked.iter().filter(|x| f(x)).count() as f32 / count;
+        let scores: Vec<f32> = checked.iter().filter_map(|x| Self::check(settings, expansions, x)).map(|x| x.score).collect();
+
+        RaidReadiness {
+            checked: checked.len(),
+            average_score: if scores.is_empty() { 0.0 } else { scores.iter().sum::<f32>() / scores.len() as f32 },
+            average_ilvl: checked.iter().map(|x| x.ilvl as f32).sum::<f32>() / count,
+            fully_enchanted: share(&|x| x.bad_gear.is_empty()),
+            sockets_filled: share(&|x| x.bad_socket.is_empty()),
+            special_items: share(&|x| x.bad_special_item.is_empty()),
+            four_piece: share(&|x| x.tier_count >= 4),
+            embellished: share(&|x| x.num_embelishments >= x.requirements(&settings.current_preset).embelishments),
+            all_required_kills: share(&|x| RuleChecker::missing_kills(settings, x) == 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checker::armory_checker::CharacterGear, config::{expansion_config::{Expansion, ItemData}, readiness::ReadinessWeights}};
+
+    // Slot data for the gear the test players wear, two-handers are listed under weapon like the real expansion data.
+    fn expansions() -> ExpansionsConfig {
+        let slot = |slot: &str, sub_slots: &[&str]| ItemData {
+            slot: slot.to_owned(),
+            sub_slots: sub_slots.iter().map(|x| x.to_string()).collect(),
+            ..ItemData::default()
+        };
+        ExpansionsConfig {
+            latest_expansion: Some(Expansion {
+                slot_data: vec![slot("head", &[]), slot("neck", &[]), slot("chest", &[]), slot("cloak", &[]), slot("finger", &[]), slot("weapon", &["twohweapon"])],
+                ..Expansion::default()
+            }),
+            ..ExpansionsConfig::default()
+        }
+    }
+
+    fn gear(slot: &str, gear_type: &str) -> CharacterGear {
+        serde_json::from_str(&format!(r#"{{"id":1,"inventory_type":{{"name":"{}","type":"{}"}}}}"#, slot, gear_type)).unwrap()
+    }
+
+    fn player(ilvl: i32, tier_count: i32) -> PlayerData {
+        let mut player = PlayerData { num_embelishments: 2, lvl: 80, ilvl, tier_count, ..PlayerData::for_test("1") };
+        for (slot, gear_type) in [("head", "HEAD"), ("neck", "NECK"), ("chest", "CHEST"), ("back", "CLOAK"), ("finger1", "FINGER"), ("finger2", "FINGER")] {
+            player.character.gear.insert(slot.to_owned(), gear(slot, gear_type));
+        }
+        player
+    }
+
+    #[test]
+    fn score_weighs_each_part() {
+        let mut settings = Settings::default();
+        settings.current_preset.average_ilvl = 650;
+        settings.current_preset.embelishments = 2;
+        settings.current_preset.readiness_weights = ReadinessWeights { item_level: 1.0, gear: 1.0, tier: 2.0, embellishments: 0.0, kills: 1.0, buffs: 0.0 };
+        settings.current_preset.slots.back.require_slot = true;
+        settings.current_preset.slots.ring.require_slot = true;
+        settings.current_preset.slots.chest.require_slot = true;
+
+        let ready = ReadinessChecker::check(&settings, &expansions(), &player(655, 4)).unwrap();
+        assert_eq!(ready.score, 100.0);
+        assert_eq!(ready.parts.iter().map(|x| x.0).collect::<Vec<_>>(), ["Item level", "Gear", "Tier"]);
+
+        // 5 item levels short is half, one of the four required enchants (cloak, chest and both rings) missing is three quarters, 2 tier pieces is half.
+        let mut unready = player(645, 2);
+        unready.bad_gear.push("cloak is missing an enchant".to_owned());
+        let unready = ReadinessChecker::check(&settings, &expansions(), &unready).unwrap();
+        assert_eq!(unready.parts, [("Item level", 0.5), ("Gear", 0.75), ("Tier", 0.5)]);
+        assert_eq!(unready.score, (0.5 + 0.75 + 2.0 * 0.5) / 4.0 * 100.0);
+
+        settings.current_preset.readiness_weights = ReadinessWeights { item_level: 0.0, gear: 0.0, tier: 0.0, embellishments: 0.0, kills: 0.0, buffs: 0.0 };
+        assert!(ReadinessChecker::check(&settings, &expansions(), &player(655, 4)).is_none());
+
+        // Gear is left out when no slot requires anything.
+        settings.current_preset.readiness_weights = ReadinessWeights { item_level: 1.0, gear: 1.0, tier: 0.0, embellishments: 0.0, kills: 0.0, buffs: 0.0 };
+        settings.current_preset.slots = Slots::default();
+        assert!(ReadinessChecker::check(&settings, &expansions(), &player(655, 4)).unwrap().parts.iter().all(|x| x.0 != "Gear"));
+    }
+
+    #[test]
+    fn summary_counts_checked_players() {
+        let settings = Settings::default();
+        let mut skipped = player(600, 0);
+        skipped.skip_reason = Some("Not found".to_owned());
+        let mut enchant_missing = player(640, 2);
+        enchant_missing.bad_gear.push("neck is missing an enchant".to_owned());
+        let ready = player(660, 4);
+
+        let summary = ReadinessChecker::summarise(&settings, &expansions(), &[&skipped, &enchant_missing, &ready]);
+        assert_eq!(summary.checked, 2);
+        assert_eq!(summary.average_ilvl, 650.0);
+        assert_eq!(summary.fully_enchanted, 0.5);
+        assert_eq!(summary.four_piece, 0.5);
+        assert_eq!(summary.all_required_kills, 1.0);
+    }
+
+    #[test]
+    fn two_hand_weapons_count_towards_gear() {
+        let mut settings = Settings::default();
+        settings.current_preset.readiness_weights = ReadinessWeights { item_level: 0.0, gear: 1.0, tier: 0.0, embellishments: 0.0, kills: 0.0, buffs: 0.0 };
+        settings.current_preset.slots.weapon.require_slot = true;
+
+        let mut player = player(655, 4);
+        player.character.gear.insert("mainhand".to_owned(), gear("mainhand", "TWOHWEAPON"));
+        player.bad_gear.push("twohweapon is missing an enchant".to_owned());
+        let readiness = ReadinessChecker::check(&settings, &expansions(), &player).unwrap();
+        assert_eq!(readiness.parts, [("Gear", 0.0)]);
+
+        player.bad_gear.clear();
+        assert_eq!(ReadinessChecker::check(&settings, &expansions(), &player).unwrap().parts, [("Gear", 1.0)]);
+    }
+}
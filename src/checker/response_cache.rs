@@ -0,0 +1,181 @@
+use std::{fs, path::{Path, PathBuf}, time::{Duration, SystemTime}};
+
+use tracing::{error, info};
+
+use crate::config::settings::CacheSettings;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageKind {
+    Profile,
+    Achievements,
+    Reputation,
+}
+
+// Raw response bodies on disk, one file per URL. Entries older than their page kind's TTL are ignored and overwritten on the next fetch.
+pub struct ResponseCache {
+    dir: PathBuf,
+    settings: CacheSettings,
+    force_refresh: bool,
+}
+
+impl ResponseCache {
+    pub fn new(settings: &CacheSettings, force_refresh: bool) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+
+        let dir = PathBuf::from(&settings.dir);
+        if let Err(err) = fs::create_dir_all(&dir) {
+            error!("Could not create cache directory {:?}: {:?}", dir, err);
+            return None;
+        }
+
+        Some(Self {
+            dir,
+            settings: settings.clone(),
+            force_refresh,
+        })
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:x}.cache", md5::compute(url)))
+    }
+
+    // Only the files `path` names, the directory is a setting and may hold other files.
+    fn is_cache_file(path: &Path) -> bool {
+        let hashed = path.file_stem().and_then(|x| x.to_str()).is_some_and(|x| x.len() == 32 && x.chars().all(|c| c.is_ascii_hexdigit()));
+        hashed && path.extension().is_some_and(|x| x == "cache") && path.is_file()
+    }
+
+    fn ttl(&self, kind: PageKind) -> Duration {
+        let minutes = match kind {
+            PageKind::Profile => self.settings.profile_ttl_minutes,
+            PageKind::Achievements => self.settings.achievements_ttl_minutes,
+            PageKind::Reputation => self.settings.reputation_ttl_minutes,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    pub fn get(&self, url: &str, kind: PageKind) -> Option<String> {
+        if self.force_refresh {
+            return None;
+        }
+
+        let path = self.path(url);
+        let modified = fs::metadata(&path).and_then(|x| x.modified()).ok()?;
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if age >= self.ttl(kind) {
+            return None;
+        }
+
+        info!("Using cached response for {}", url);
+        fs::read_to_string(path).ok()
+    }
+
+    pub fn store(&self, url: &str, body: &str) {
+        // The directory may have been cleared from the settings while a check was running.
+        let _ = fs::create_dir_all(&self.dir);
+        if let Err(err) = fs::write(self.path(url), body) {
+            error!("Could not cache response for {}: {:?}", url, err);
+        }
+    }
+
+    // Removes the cached responses, leaving the directory and anything else in it alone.
+    pub fn clear(settings: &CacheSettings) {
+        info!("Clearing response cache in {}", settings.dir);
+        let entries = match fs::read_dir(&settings.dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    error!("Could not read cache directory {}: {:?}", settings.dir, err);
+                }
+                return;
+            },
+        };
+
+        for path in entries.flatten().map(|x| x.path()).filter(|x| Self::is_cache_file(x)) {
+            if let Err(err) = fs::remove_file(&path) {
+                error!("Could not remove cached response {:?}: {:?}", path, err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static URL: &str = "https://worldofwarcraft.blizzard.com/en-gb/character/eu/silvermoon/tester";
+
+    fn settings(name: &str) -> CacheSettings {
+        let dir = std::env::temp_dir().join(format!("raidchecker_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        CacheSettings {
+            dir: dir.to_string_lossy().to_string(),
+            ..CacheSettings::default()
+        }
+    }
+
+    #[test]
+    fn stored_response_is_returned_within_ttl() {
+        let cache = ResponseCache::new(&settings("hit"), false).unwrap();
+        assert!(cache.get(URL, PageKind::Profile).is_none());
+
+        cache.store(URL, "<html>profile</html>");
+        assert_eq!(cache.get(URL, PageKind::Profile).as_deref(), Some("<html>profile</html>"));
+        assert!(cache.get(&format!("{}/reputation", URL), PageKind::Reputation).is_none());
+    }
+
+    #[test]
+    fn ttls_are_per_page_kind() {
+        let settings = CacheSettings {
+            profile_ttl_minutes: 0,
+            ..settings("ttl")
+        };
+        let cache = ResponseCache::new(&settings, false).unwrap();
+        cache.store(URL, "body");
+        assert!(cache.get(URL, PageKind::Profile).is_none());
+        assert!(cache.get(URL, PageKind::Reputation).is_some());
+    }
+
+    #[test]
+    fn force_refresh_skips_reads_but_still_stores() {
+        let settings = settings("force");
+        let forced = ResponseCache::new(&settings, true).unwrap();
+        forced.store(URL, "fresh");
+        assert!(forced.get(URL, PageKind::Profile).is_none());
+
+        let cache = ResponseCache::new(&settings, false).unwrap();
+        assert_eq!(cache.get(URL, PageKind::Profile).as_deref(), Some("fresh"));
+
+        ResponseCache::clear(&settings);
+        assert!(cache.get(URL, PageKind::Profile).is_none());
+    }
+
+    #[test]
+    fn clear_only_removes_cached_responses() {
+        let settings = settings("clear");
+        let cache = ResponseCache::new(&settings, false).unwrap();
+        cache.store(URL, "body");
+        let dir = PathBuf::from(&settings.dir);
+        fs::write(dir.join("notes.txt"), "keep me").unwrap();
+        fs::write(dir.join("backup.cache"), "keep me too").unwrap();
+        fs::create_dir_all(dir.join("photos")).unwrap();
+
+        ResponseCache::clear(&settings);
+        assert!(cache.get(URL, PageKind::Profile).is_none());
+        assert_eq!(fs::read_to_string(dir.join("notes.txt")).unwrap(), "keep me");
+        assert!(dir.join("backup.cache").exists());
+        assert!(dir.join("photos").is_dir());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn disabled_cache_is_none() {
+        let settings = CacheSettings {
+            enabled: false,
+            ..settings("disabled")
+        };
+        assert!(ResponseCache::new(&settings, false).is_none());
+    }
+}
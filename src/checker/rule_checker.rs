@@ -0,0 +1,349 @@
+use chrono::Utc;
+
+use crate::{checker::{check_player::PlayerData, saved_checker::ResetSchedule}, config::{rules::{Rule, RuleCondition, RuleField, RuleOp, RuleValue, Severity}, settings::Settings, waivers::WaiverTarget}};
+
+pub struct RuleChecker;
+
+// A rule a player matches. The sign-up list and the player info are both built from these, so they always agree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerIssue {
+    pub category: String,
+    pub severity: Severity,
+    pub message: String,
+    pub colour: [u8; 4],
+    pub waived: Option<String>, // The waivers letting it through, shown instead of counting it
+}
+
+impl PlayerIssue {
+    pub fn is_waived(&self) -> bool {
+        self.waived.is_some()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum FieldValue {
+    Number(i32),
+    Text(String),
+}
+
+impl RuleChecker {
+    // Kills in the event's lockout on bosses the preset checks saves for, counting each difficulty.
+    pub fn saved_kills(settings: &Settings, player: &PlayerData, event_start: Option<i64>) -> i32 {
+        let lockout = ResetSchedule::for_region(settings.current_preset.region).lockout_for_event(event_start);
+        let mut kills = 0;
+        for (raid_id, raid) in player.raid_data.iter() {
+            let Some(saved_raid) = settings.current_preset.saved_raids.get(&(*raid_id as i32)) else {
+                continue;
+            };
+            for boss in raid.bosses.values() {
+                for (difficulty_id, difficulty) in boss.difficulties.iter() {
+                    let saved = saved_raid.difficulty.get(&(*difficulty_id as i32)).is_some_and(|x| x.boss_ids.get(boss.boss_id).is_some());
+                    if saved && difficulty.boss_kill_time.is_some_and(|x| lockout.contains(x)) {
+                        kills += 1;
+                    }
+                }
+            }
+        }
+        kills
+    }
+
+    // Required bosses the player has killed and how many are required, counting each difficulty.
+    pub fn required_kills(settings: &Settings, player: &PlayerData) -> (i32, i32) {
+        let (mut killed, mut required) = (0, 0);
+        for (raid_id, raid) in player.raid_data.iter() {
+            let Some(required_raid) = settings.current_preset.required_raids.get(&(*raid_id as i32)) else {
+                continue;
+            };
+            for boss in raid.bosses.values() {
+                for (difficulty_id, difficulty) in boss.difficulties.iter() {
+                    if required_raid.difficulty.get(&(*difficulty_id as i32)).is_some_and(|x| x.boss_ids.get(boss.boss_id).is_some()) {
+                        required += 1;
+                        if difficulty.killed_before {
+                            killed += 1;
+                        }
+                    }
+                }
+            }
+        }
+        (killed, required)
+    }
+
+    // Required bosses the player has never killed, counting each difficulty.
+    pub fn missing_kills(settings: &Settings, player: &PlayerData) -> i32 {
+        let (killed, required) = Self::required_kills(settings, player);
+        required - killed
+    }
+
+    // None when the armory didn't tell, a rule can't match on something that isn't known.
+    fn field_value(settings: &Settings, player: &PlayerData, event_start: Option<i64>, field: RuleField) -> Option<FieldValue> {
+        let count = |x: usize| Some(FieldValue::Number(x as i32));
+        match field {
+            RuleField::ItemLevel => Some(FieldValue::Number(player.ilvl)),
+            RuleField::Level => Some(FieldValue::Number(player.lvl as i32)),
+            RuleField::TierCount => (player.tier_count != -1).then_some(FieldValue::Number(player.tier_count)),
+            RuleField::Embellishments => (player.num_embelishments != -1).then_some(FieldValue::Number(player.num_embelishments)),
+            RuleField::SavedKills => Some(FieldValue::Number(Self::saved_kills(settings, player, event_start))),
+            RuleField::MissingKills => Some(FieldValue::Number(Self::missing_kills(settings, player))),
+            RuleField::MissingBuffs => count(player.buff_status.values().filter(|x| x.1 > 0).count()),
+            RuleField::BadEnchants => count(player.bad_gear.len()),
+            RuleField::MissingSockets => count(player.bad_socket.len()),
+            RuleField::MissingSpecialItems => count(player.bad_special_item.len()),
+            RuleField::Role => Some(FieldValue::Text(player.role_name.clone())),
+            RuleField::Class => Some(FieldValue::Text(player.display_class().to_owned())),
+            RuleField::Spec => Some(FieldValue::Text(player.spec_name.clone())),
+        }
+    }
+
+    fn condition_matches(settings: &Settings, player: &PlayerData, event_start: Option<i64>, condition: &RuleCondition) -> bool {
+        let Some(value) = Self::field_value(settings, player, event_start, condition.field) else {
+            return false;
+        };
+        let expected = match &condition.value {
+            RuleValue::Number(number) => FieldValue::Number(*number),
+            RuleValue::Text(text) => FieldValue::Text(text.clone()),
+            RuleValue::RequiredItemLevel => FieldValue::Number(player.requirements(&settings.current_preset).average_ilvl),
+            RuleValue::RequiredEmbellishments => FieldValue::Number(player.requirements(&settings.current_preset).embelishments),
+        };
+
+        match (value, expected) {
+            (FieldValue::Number(value), FieldValue::Number(expected)) => match condition.op {
+                RuleOp::Less => value < expected,
+                RuleOp::LessOrEqual => value <= expected,
+                RuleOp::Equal => value == expected,
+                RuleOp::NotEqual => value != expected,
+                RuleOp::GreaterOrEqual => value >= expected,
+                RuleOp::Greater => value > expected,
+            },
+            // Text is only ever the same or different, and case doesn't matter.
+            (FieldValue::Text(value), FieldValue::Text(expected)) => match condition.op {
+                RuleOp::Equal => value.eq_ignore_ascii_case(&expected),
+                RuleOp::NotEqual => !value.eq_ignore_ascii_case(&expected),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn matches(settings: &Settings, player: &PlayerData, event_start: Option<i64>, rule: &Rule) -> bool {
+        if !rule.enabled || rule.conditions.is_empty() {
+            return false;
+        }
+
+        let mut conditions = rule.conditions.iter().map(|x| Self::condition_matches(settings, player, event_start, x));
+        if rule.match_any {
+            conditions.any(|x| x)
+        } else {
+            conditions.all(|x| x)
+        }
+    }
+
+    // The player without the gear issues on slots they have an active waiver for, None if there aren't any.
+    fn without_waived_slots(settings: &Settings, player: &PlayerData, at: i64) -> Option<PlayerData> {
+        let waived_slots: Vec<&str> = settings.current_preset.waivers.get(&player.discord_id)?.iter()
+            .filter(|x| x.is_active(at))
+            .filter_map(|x| match &x.target {
+                WaiverTarget::Slot(slot) => Some(slot.as_str()),
+                WaiverTarget::Category(_) => None,
+            })
+            .collect();
+        if waived_slots.is_empty() {
+            return None;
+        }
+
+        // Gear issues start with the slot they're on.
+        let unwaived = |issues: &[String]| issues.iter()
+            .filter(|x| !x.split_whitespace().next().is_some_and(|slot| waived_slots.iter().any(|y| y.eq_ignore_ascii_case(slot))))
+            .cloned()
+            .collect::<Vec<String>>();
+        let mut player = player.clone();
+        player.bad_gear = unwaived(&player.bad_gear);
+        player.bad_socket = unwaived(&player.bad_socket);
+        player.bad_special_item = unwaived(&player.bad_special_item);
+        Some(player)
+    }
+
+    // Every rule the player matches in the preset's order. The first one that isn't waived decides their colour.
+    pub fn issues(settings: &Settings, player: &PlayerData, event_start: Option<i64>) -> Vec<PlayerIssue> {
+        let at = event_start.unwrap_or_else(|| Utc::now().timestamp());
+        let waivers: Vec<_> = settings.current_preset.waivers.get(&player.discord_id).into_iter().flatten().filter(|x| x.is_active(at)).collect();
+        let unwaived_player = Self::without_waived_slots(settings, player, at);
+
+        settings.current_preset.rules.iter()
+            .filter(|x| Self::matches(settings, player, event_start, x))
+            .map(|rule| {
+                // Waived by name, or only matched because of slots that are waived.
+                let slot_waived = unwaived_player.as_ref().is_some_and(|x| !Self::matches(settings, x, event_start, rule));
+                let covering: Vec<String> = waivers.iter()
+                    .filter(|x| match &x.target {
+                        WaiverTarget::Category(category) => *category == rule.name,
+                        WaiverTarget::Slot(_) => slot_waived,
+                    })
+                    .map(|x| x.description())
+                    .collect();
+
+                // The numbers the rule looked at, so the message says by how much.
+                let mut details: Vec<String> = Vec::new();
+                for condition in rule.conditions.iter() {
+                    if let Some(FieldValue::Number(value)) = Self::field_value(settings, player, event_start, condition.field) {
+                        let detail = format!("{} {}", condition.field.as_str(), value);
+                        if !details.contains(&detail) {
+                            details.push(detail);
+                        }
+                    }
+                }
+                let message = if details.is_empty() {
+                    rule.message().to_owned()
+                } else {
+                    format!("{} ({})", rule.message(), details.join(", "))
+                };
+
+                PlayerIssue {
+                    category: rule.name.clone(),
+                    severity: rule.severity,
+                    message,
+                    colour: rule.colour,
+                    waived: if covering.is_empty() { None } else { Some(covering.join(", ")) },
+                }
+            })
+            .collect()
+    }
+
+    // Whether an issue that isn't waived came from a rule looking at the field.
+    pub fn flags(settings: &Settings, issues: &[PlayerIssue], field: RuleField) -> bool {
+        issues.iter().filter(|x| !x.is_waived()).any(|issue| {
+            settings.current_preset.rules.iter().any(|rule| rule.name == issue.category && rule.conditions.iter().any(|x| x.field == field))
+        })
+    }
+
+    // The first of the preset's rules the player matches.
+    pub fn first_match<'a>(settings: &'a Settings, player: &PlayerData, event_start: Option<i64>) -> Option<&'a Rule> {
+        settings.current_preset.rules.iter().find(|x| Self::matches(settings, player, event_start, x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{rules::default_rules, waivers::Waiver};
+
+    fn player() -> PlayerData {
+        PlayerData {
+            num_embelishments: -1,
+            ilvl: 640,
+            lvl: 80,
+            tier_count: -1,
+            role_name: "tank".to_owned(),
+            ..PlayerData::for_test("1")
+        }
+    }
+
+    #[test]
+    fn default_rules_follow_the_old_priority() {
+        let mut settings = Settings::default();
+        settings.current_preset.average_ilvl = 650;
+        settings.current_preset.embelishments = 2;
+        assert_eq!(settings.current_preset.rules, default_rules());
+
+        let mut player = player();
+        player.bad_socket.push("Neck".to_owned());
+        assert_eq!(RuleChecker::first_match(&settings, &player, None).map(|x| x.name.as_str()), Some("Bad Item Level"));
+
+        player.ilvl = 660;
+        assert_eq!(RuleChecker::first_match(&settings, &player, None).map(|x| x.name.as_str()), Some("Sockets Missing"));
+
+        // Embellishments are only a problem once they're known.
+        player.num_embelishments = 1;
+        assert_eq!(RuleChecker::first_match(&settings, &player, None).map(|x| x.name.as_str()), Some("Gear enchantment issue"));
+
+        player.num_embelishments = 2;
+        player.bad_socket.clear();
+        assert!(RuleChecker::first_match(&settings, &player, None).is_none());
+    }
+
+    #[test]
+    fn every_matching_rule_is_an_issue() {
+        let mut settings = Settings::default();
+        settings.current_preset.average_ilvl = 650;
+        let mut player = player();
+        player.tier_count = 2;
+        player.bad_gear.push("Chest".to_owned());
+
+        let issues = RuleChecker::issues(&settings, &player, None);
+        let categories: Vec<&str> = issues.iter().map(|x| x.category.as_str()).collect();
+        assert_eq!(categories, ["Bad Item Level", "Gear enchantment issue", "Missing Tier"]);
+        assert_eq!(issues[0].severity, Severity::Blocker);
+        assert_eq!(issues[0].message, "Item level is below the requirement. (Item level 640)");
+        assert_eq!(issues[2].severity, Severity::Info);
+        assert_eq!(issues[2].message, "Has less than 4 tier pieces. (Tier pieces 2)");
+        assert_eq!(RuleChecker::first_match(&settings, &player, None).map(|x| x.colour), Some(issues[0].colour));
+    }
+
+    #[test]
+    fn waived_issues_are_kept_but_marked() {
+        let mut settings = Settings::default();
+        settings.current_preset.average_ilvl = 650;
+        let mut player = player();
+        player.bad_socket.push("finger is missing 1 socket".to_owned());
+        player.bad_gear.push("neck is missing an enchant".to_owned());
+
+        let waiver = |target, expires| Waiver { target, expires, note: "Trial".to_owned() };
+        settings.current_preset.waivers.insert(player.discord_id.clone(), vec![
+            waiver(WaiverTarget::Category("Bad Item Level".to_owned()), None),
+            waiver(WaiverTarget::Slot("finger".to_owned()), Some(2_000)),
+        ]);
+
+        let issues = RuleChecker::issues(&settings, &player, Some(1_000));
+        let waived: Vec<(&str, bool)> = issues.iter().map(|x| (x.category.as_str(), x.is_waived())).collect();
+        assert_eq!(waived, [("Bad Item Level", true), ("Gear enchantment issue", false), ("Sockets Missing", true)]);
+        assert_eq!(issues[0].waived.as_deref(), Some("Bad Item Level (Trial)"));
+
+        // Once it expires the socket counts again.
+        let issues = RuleChecker::issues(&settings, &player, Some(2_000));
+        assert!(!issues[2].is_waived());
+    }
+
+    #[test]
+    fn only_unwaived_issues_flag_their_fields() {
+        let mut settings = Settings::default();
+        settings.current_preset.average_ilvl = 650;
+        let mut player = player();
+        player.bad_gear.push("neck is missing an enchant".to_owned());
+
+        let issues = RuleChecker::issues(&settings, &player, None);
+        assert!(RuleChecker::flags(&settings, &issues, RuleField::ItemLevel));
+        assert!(RuleChecker::flags(&settings, &issues, RuleField::BadEnchants));
+        assert!(!RuleChecker::flags(&settings, &issues, RuleField::MissingKills));
+
+        settings.current_preset.waivers.insert(player.discord_id.clone(), vec![
+            Waiver { target: WaiverTarget::Category("Bad Item Level".to_owned()), expires: None, note: String::new() },
+        ]);
+        settings.current_preset.rules.iter_mut().find(|x| x.name == "Gear enchantment issue").unwrap().enabled = false;
+        let issues = RuleChecker::issues(&settings, &player, None);
+        assert!(!RuleChecker::flags(&settings, &issues, RuleField::ItemLevel));
+        assert!(!RuleChecker::flags(&settings, &issues, RuleField::BadEnchants));
+    }
+
+    #[test]
+    fn custom_rules_match_roles_and_numbers() {
+        let mut settings = Settings::default();
+        settings.current_preset.rules = vec![Rule {
+            name: "Tank item level".to_owned(),
+            conditions: vec![
+                RuleCondition::new(RuleField::Role, RuleOp::Equal, RuleValue::Text("Tank".to_owned())),
+                RuleCondition::new(RuleField::ItemLevel, RuleOp::Less, RuleValue::Number(645)),
+            ],
+            severity: Severity::Blocker,
+            ..Rule::default()
+        }];
+
+        let mut player = player();
+        assert!(RuleChecker::first_match(&settings, &player, None).is_some());
+        player.role_name = "healer".to_owned();
+        assert!(RuleChecker::first_match(&settings, &player, None).is_none());
+
+        settings.current_preset.rules[0].match_any = true;
+        assert!(RuleChecker::first_match(&settings, &player, None).is_some());
+        settings.current_preset.rules[0].enabled = false;
+        assert!(RuleChecker::first_match(&settings, &player, None).is_none());
+    }
+}
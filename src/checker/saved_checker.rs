@@ -1,27 +1,82 @@
 use std::{collections::BTreeMap, usize};
 
-use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
-use tracing::debug;
+use chrono::{DateTime, Datelike, Duration, Utc, Weekday};
 
-use crate::{checker::armory_checker::{ArmoryCharacterResponse, PlayerRaidBossData, PlayerRaidBossDifficultyData, PlayerRaidData}, config::settings::RequiredRaid};
+use crate::{checker::armory_checker::{ArmoryCharacterResponse, PlayerRaidBossData, PlayerRaidBossDifficultyData, PlayerRaidData}, config::region::Region};
 
 pub struct SavedChecker {}
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lockout {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Lockout {
+    pub fn next(&self) -> Lockout {
+        Lockout {
+            start: self.end,
+            end: self.end + Duration::weeks(1),
+        }
+    }
+
+    // Armory kill timestamps are in milliseconds.
+    pub fn contains(&self, timestamp_millis: u64) -> bool {
+        let timestamp = timestamp_millis as i64;
+        timestamp >= self.start.timestamp_millis() && timestamp < self.end.timestamp_millis()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResetSchedule {
+    pub weekday: Weekday,
+    pub hour: u32,
+}
+
+impl ResetSchedule {
+    // Resets are pinned to UTC, so they do not move when local clocks change for daylight saving.
+    pub fn for_region(region: Region) -> Self {
+        match region {
+            Region::EU => Self { weekday: Weekday::Wed, hour: 4 },
+            Region::US => Self { weekday: Weekday::Tue, hour: 15 },
+            Region::KR => Self { weekday: Weekday::Wed, hour: 23 }, // Thursday 08:00 KST
+            Region::TW => Self { weekday: Weekday::Thu, hour: 0 },  // Thursday 08:00 CST
+        }
+    }
+
+    pub fn lockout_at(&self, at: DateTime<Utc>) -> Lockout {
+        let days_to_subtract = (7 + at.weekday().num_days_from_monday() as i64 - self.weekday.num_days_from_monday() as i64) % 7;
+        let mut start = (at.date_naive() - Duration::days(days_to_subtract)).and_hms_opt(self.hour, 0, 0).unwrap().and_utc();
+
+        // Reset day, but before the reset hour, still belongs to last week's lockout.
+        if start > at {
+            start -= Duration::weeks(1);
+        }
+
+        Lockout {
+            start,
+            end: start + Duration::weeks(1),
+        }
+    }
+
+    pub fn current_lockout(&self) -> Lockout {
+        self.lockout_at(Utc::now())
+    }
+
+    pub fn next_lockout(&self) -> Lockout {
+        self.current_lockout().next()
+    }
+
+    // Raid-helper start times are unix seconds. Falls back to the current lockout when the event has no start time.
+    pub fn lockout_for_event(&self, event_start: Option<i64>) -> Lockout {
+        match event_start.and_then(|start| DateTime::from_timestamp(start, 0)) {
+            Some(start) => self.lockout_at(start),
+            None => self.current_lockout(),
+        }
+    }
+}
+
 impl SavedChecker {
-    pub fn get_wednesday_reset_timestamp() -> i64 {
-        let now = Utc::now();
-        let weekday = now.weekday();
-    
-        let days_to_subtract = match weekday {
-            Weekday::Wed => 0,
-            _ => (7 + weekday.num_days_from_monday() as i64 - 2) % 7,
-        };
-    
-        let wednesday_date = now.date_naive() - Duration::days(days_to_subtract);
-        let wednesday_4am = wednesday_date.and_hms_opt(4, 0, 0).unwrap();
-        wednesday_4am.and_utc().timestamp_millis()
-    }
-    
     pub fn check_bosses(
         armory: &ArmoryCharacterResponse,
         raid_data: &mut BTreeMap<usize, PlayerRaidData>,
@@ -42,4 +97,101 @@ impl SavedChecker {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn eu_lockout_starts_wednesday_0400() {
+        let schedule = ResetSchedule::for_region(Region::EU);
+        // Friday 2025-03-14
+        let lockout = schedule.lockout_at(utc(2025, 3, 14, 12, 0));
+        assert_eq!(lockout.start, utc(2025, 3, 12, 4, 0));
+        assert_eq!(lockout.end, utc(2025, 3, 19, 4, 0));
+    }
+
+    #[test]
+    fn eu_reset_day_before_reset_is_previous_lockout() {
+        let schedule = ResetSchedule::for_region(Region::EU);
+        let lockout = schedule.lockout_at(utc(2025, 3, 12, 3, 59));
+        assert_eq!(lockout.start, utc(2025, 3, 5, 4, 0));
+
+        let lockout = schedule.lockout_at(utc(2025, 3, 12, 4, 0));
+        assert_eq!(lockout.start, utc(2025, 3, 12, 4, 0));
+    }
+
+    #[test]
+    fn us_lockout_starts_tuesday_1500() {
+        let schedule = ResetSchedule::for_region(Region::US);
+        let lockout = schedule.lockout_at(utc(2025, 3, 11, 14, 59));
+        assert_eq!(lockout.start, utc(2025, 3, 4, 15, 0));
+
+        let lockout = schedule.lockout_at(utc(2025, 3, 11, 15, 0));
+        assert_eq!(lockout.start, utc(2025, 3, 11, 15, 0));
+        assert_eq!(lockout.end, utc(2025, 3, 18, 15, 0));
+
+        // Monday belongs to the lockout that started the previous Tuesday.
+        let lockout = schedule.lockout_at(utc(2025, 3, 17, 23, 0));
+        assert_eq!(lockout.start, utc(2025, 3, 11, 15, 0));
+    }
+
+    #[test]
+    fn kr_and_tw_resets_fall_on_thursday_morning_local_time() {
+        let kr = ResetSchedule::for_region(Region::KR).lockout_at(utc(2025, 3, 13, 12, 0));
+        assert_eq!(kr.start, utc(2025, 3, 12, 23, 0));
+
+        let tw = ResetSchedule::for_region(Region::TW).lockout_at(utc(2025, 3, 13, 12, 0));
+        assert_eq!(tw.start, utc(2025, 3, 13, 0, 0));
+
+        let tw = ResetSchedule::for_region(Region::TW).lockout_at(utc(2025, 3, 12, 23, 59));
+        assert_eq!(tw.start, utc(2025, 3, 6, 0, 0));
+    }
+
+    #[test]
+    fn eu_lockout_spanning_dst_start_is_one_week_of_utc() {
+        // Europe moves clocks forward on 2025-03-30.
+        let schedule = ResetSchedule::for_region(Region::EU);
+        let lockout = schedule.lockout_at(utc(2025, 3, 30, 2, 30));
+        assert_eq!(lockout.start, utc(2025, 3, 26, 4, 0));
+        assert_eq!(lockout.end, utc(2025, 4, 2, 4, 0));
+        assert_eq!(lockout.end - lockout.start, Duration::weeks(1));
+    }
+
+    #[test]
+    fn us_lockout_spanning_dst_end_is_one_week_of_utc() {
+        // US moves clocks back on 2025-11-02.
+        let schedule = ResetSchedule::for_region(Region::US);
+        let lockout = schedule.lockout_at(utc(2025, 11, 2, 8, 30));
+        assert_eq!(lockout.start, utc(2025, 10, 28, 15, 0));
+        assert_eq!(lockout.next().start, utc(2025, 11, 4, 15, 0));
+        assert_eq!(lockout.next().end, utc(2025, 11, 11, 15, 0));
+    }
+
+    #[test]
+    fn event_lockout_uses_event_start() {
+        let schedule = ResetSchedule::for_region(Region::EU);
+        // Event on Thursday 2025-03-20, checked the week before.
+        let lockout = schedule.lockout_for_event(Some(utc(2025, 3, 20, 19, 30).timestamp()));
+        assert_eq!(lockout.start, utc(2025, 3, 19, 4, 0));
+        assert!(!lockout.contains(utc(2025, 3, 14, 20, 0).timestamp_millis() as u64));
+
+        assert_eq!(schedule.lockout_for_event(None), schedule.current_lockout());
+    }
+
+    #[test]
+    fn lockout_contains_is_start_inclusive_end_exclusive() {
+        let lockout = ResetSchedule::for_region(Region::EU).lockout_at(utc(2025, 3, 14, 12, 0));
+        assert!(lockout.contains(lockout.start.timestamp_millis() as u64));
+        assert!(lockout.contains(utc(2025, 3, 19, 3, 59).timestamp_millis() as u64));
+        assert!(!lockout.contains(lockout.end.timestamp_millis() as u64));
+        assert!(!lockout.contains(utc(2025, 3, 12, 3, 59).timestamp_millis() as u64));
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{config::settings::RaidHelperSettings, http_client};
+
+#[derive(Deserialize)]
+struct ServerEventsResponse {
+    #[serde(rename = "postedEvents", default)]
+    posted_events: Vec<ServerEvent>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ServerEvent {
+    pub id: String,
+    #[serde(alias = "displayTitle", default)]
+    pub title: String,
+    #[serde(rename = "startTime", default)]
+    pub start_time: i64,
+    #[serde(rename = "signUpCount", alias = "signUpsAmount", default)]
+    pub signup_count: u32,
+}
+
+pub fn api_url(settings: &RaidHelperSettings) -> &str {
+    settings.api_url.trim_end_matches('/')
+}
+
+// The URL a sign-up check of the event fetches, same as pasting the event's link.
+pub fn event_url(settings: &RaidHelperSettings, event_id: &str) -> String {
+    format!("{}/v4/events/{}", api_url(settings), event_id)
+}
+
+// Upcoming events posted on the server, soonest first.
+pub async fn fetch_server_events(settings: &RaidHelperSettings) -> Result<Vec<ServerEvent>, String> {
+    if settings.server_id.trim().is_empty() {
+        return Err("No server ID set.".to_owned());
+    }
+
+    info!("Getting upcoming events for server {}", settings.server_id);
+    let now = Utc::now().timestamp();
+    let client = http_client::client();
+    let mut request = client
+        .get(format!("{}/v3/servers/{}/events", api_url(settings), settings.server_id.trim()))
+        .header("StartTimeFilter", now.to_string());
+    if !settings.api_key.is_empty() {
+        request = request.header("Authorization", settings.api_key.trim());
+    }
+
+    let response = client.send(request).await.map_err(|err| format!("Failed to get events: {:?}", err))?;
+    if !response.status().is_success() {
+        return Err(format!("Raid-helper returned {} for server {}, check the server ID and API key.", response.status(), settings.server_id));
+    }
+
+    let response: ServerEventsResponse = response.json().await.map_err(|err| format!("Failed to parse events: {:?}", err))?;
+    let mut events: Vec<ServerEvent> = response.posted_events.into_iter().filter(|x| x.start_time >= now).collect();
+    events.sort_by_key(|x| x.start_time);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::{block_on, test_server::{self, MockResponse}};
+
+    fn start_mock_server() -> String {
+        let future = Utc::now().timestamp() + 3600;
+        let body = format!(r#"{{"pages":1,"postedEvents":[
+            {{"id":"later","title":"Heroic","startTime":{},"signUpCount":25}},
+            {{"id":"past","title":"Last week","startTime":1000,"signUpCount":20}},
+            {{"id":"sooner","title":"Normal","startTime":{},"signUpCount":12}}]}}"#, future + 60, future).replace('\n', "");
+
+        test_server::start(move |request| {
+            let authorized = request.headers.iter().any(|x| x == "authorization: secret");
+            match (request.path.as_str(), authorized) {
+                ("/v3/servers/1234/events", true) => MockResponse::new("200 OK", &body),
+                ("/v3/servers/1234/events", false) => MockResponse::new("401 Unauthorized", "{}"),
+                _ => MockResponse::new("404 Not Found", "{}"),
+            }
+        })
+    }
+
+    #[test]
+    fn lists_upcoming_events_soonest_first() {
+        let settings = RaidHelperSettings::for_test(start_mock_server(), "secret");
+        let events = block_on(fetch_server_events(&settings)).unwrap();
+
+        assert_eq!(events.iter().map(|x| x.id.as_str()).collect::<Vec<_>>(), ["sooner", "later"]);
+        assert_eq!(events[0].title, "Normal");
+        assert_eq!(events[0].signup_count, 12);
+        assert_eq!(event_url(&settings, &events[0].id), format!("{}/v4/events/sooner", settings.api_url));
+    }
+
+    #[test]
+    fn rejected_key_is_an_error() {
+        let settings = RaidHelperSettings::for_test(start_mock_server(), "wrong");
+        assert!(block_on(fetch_server_events(&settings)).unwrap_err().contains("401"));
+        assert!(block_on(fetch_server_events(&RaidHelperSettings::default())).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::checker::{check_player::PlayerData, raid_sheet::Player};
+
+// Imported sign-ups are checked like a raid-helper event, under a URL that can't be fetched.
+pub static IMPORT_URL_PREFIX: &str = "import:";
+
+pub fn import_url(name: &str) -> String {
+    format!("{}{}", IMPORT_URL_PREFIX, name.trim())
+}
+
+pub fn is_import_url(url: &str) -> bool {
+    url.starts_with(IMPORT_URL_PREFIX)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Name,
+    Role,
+    Spec,
+    Discord,
+    Unused,
+}
+
+impl Column {
+    fn from_header(header: &str) -> Option<Column> {
+        match header.to_lowercase().replace([' ', '_', '-'], "").as_str() {
+            "name" | "character" | "player" => Some(Column::Name),
+            "role" => Some(Column::Role),
+            "spec" | "specialization" | "specialisation" => Some(Column::Spec),
+            "discord" | "discordid" | "userid" => Some(Column::Discord),
+            _ => None,
+        }
+    }
+}
+
+// The role names the sign-up view groups by, as raid-helper would send them.
+fn parse_role(role: &str) -> Option<&'static str> {
+    match role.to_lowercase().as_str() {
+        "" | "dps" | "damage" => Some("DPS"),
+        "tank" | "tanks" => Some("Tank"),
+        "heal" | "heals" | "healer" | "healers" => Some("Healer"),
+        "melee" => Some("Melee"),
+        "range" | "ranged" => Some("Ranged"),
+        _ => None,
+    }
+}
+
+fn split_line(line: &str, delimiter: Option<char>) -> Vec<String> {
+    let cells: Vec<&str> = match delimiter {
+        Some(delimiter) => line.split(delimiter).collect(),
+        None => vec![line],
+    };
+    cells.iter().map(|x| x.trim().trim_matches('"').trim().to_owned()).collect()
+}
+
+// Sign-ups from a CSV/TSV file or pasted `Name-Realm` lines. Columns are name, role, spec and Discord ID in that order,
+// unless the first line is a header naming them. Everyone is a primary sign-up, as DPS if no role is given.
+pub fn parse_signups(text: &str) -> Result<Vec<Player>, String> {
+    let lines: Vec<(usize, &str)> = text.lines().enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|x| !x.1.is_empty() && !x.1.starts_with('#'))
+        .collect();
+
+    let delimiter = if lines.iter().any(|x| x.1.contains('\t')) {
+        Some('\t')
+    } else if lines.iter().any(|x| x.1.contains(',')) {
+        Some(',')
+    } else {
+        None
+    };
+
+    let mut columns = vec![Column::Name, Column::Role, Column::Spec, Column::Discord];
+    let mut rows = &lines[..];
+    if let Some((_, first)) = lines.first() {
+        let headers = split_line(first, delimiter);
+        if headers.iter().any(|x| Column::from_header(x) == Some(Column::Name)) {
+            columns = headers.iter().map(|x| Column::from_header(x).unwrap_or(Column::Unused)).collect();
+            rows = &lines[1..];
+        }
+    }
+
+    let mut players: Vec<Player> = Vec::new();
+    for (line_number, line) in rows.iter() {
+        let cells = split_line(line, delimiter);
+        let cell = |column: Column| columns.iter().position(|x| *x == column).and_then(|x| cells.get(x)).cloned().unwrap_or_default();
+
+        let name = cell(Column::Name);
+        if name.is_empty() {
+            return Err(format!("Line {}: no character name.", line_number));
+        }
+        let role = cell(Column::Role);
+        let Some(role) = parse_role(&role) else {
+            return Err(format!("Line {}: unknown role \"{}\", use tank, healer, melee, ranged or dps.", line_number, role));
+        };
+        if players.iter().any(|x| x.name.eq_ignore_ascii_case(&name)) {
+            return Err(format!("Line {}: {} is listed twice.", line_number, name));
+        }
+
+        let spec = cell(Column::Spec);
+        let discord = cell(Column::Discord);
+        // Players are told apart by their Discord ID, an alt on the same ID would replace the other character.
+        if let Some(other) = players.iter().find(|x| !discord.is_empty() && x.userId == discord) {
+            return Err(format!("Line {}: Discord ID {} is already used by {}, list one character per person.", line_number, discord, other.name));
+        }
+        players.push(Player {
+            position: players.len() as i32,
+            specName: if spec.is_empty() { None } else { Some(spec) },
+            // Without a Discord ID the name stands in, so rechecking the same list still recognises everyone.
+            userId: if discord.is_empty() { format!("import-{}", name.to_lowercase()) } else { discord },
+            name,
+            roleName: Some(role.to_owned()),
+            className: role.to_owned(),
+            status: "primary".to_owned(),
+        });
+    }
+
+    if players.is_empty() {
+        return Err("No sign-ups found.".to_owned());
+    }
+    Ok(players)
+}
+
+// Rechecking an import has no file to go back to, the sign-ups are rebuilt from the results instead.
+pub fn signups_from_results(players: &[PlayerData]) -> Vec<Player> {
+    players.iter().enumerate().map(|(index, player)| Player {
+        position: index as i32,
+        specName: if player.spec_name.is_empty() { None } else { Some(player.spec_name.clone()) },
+        name: player.name.clone(),
+        roleName: Some(player.role_name.clone()),
+        className: player.class_name.clone(),
+        userId: player.discord_id.clone(),
+        status: player.status.clone(),
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_csv_and_tsv() {
+        let pasted = parse_signups("Thrall-Draenor\n\n# bench\nJaina-Silvermoon\n").unwrap();
+        assert_eq!(pasted.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(), ["Thrall-Draenor", "Jaina-Silvermoon"]);
+        assert_eq!(pasted[0].className, "DPS");
+        assert_eq!(pasted[1].userId, "import-jaina-silvermoon");
+        assert_eq!(pasted[1].position, 1);
+
+        let csv = parse_signups("Thrall-Draenor,Tank,Protection,123\nJaina-Silvermoon,heals").unwrap();
+        assert_eq!(csv[0].className, "Tank");
+        assert_eq!(csv[0].specName.as_deref(), Some("Protection"));
+        assert_eq!(csv[0].userId, "123");
+        assert_eq!(csv[1].roleName.as_deref(), Some("Healer"));
+        assert_eq!(csv[1].specName, None);
+
+        // Headers can come in any order and have extra columns.
+        let tsv = parse_signups("Discord ID\tNote\tName\tRole\n\"456\"\tlate\tThrall-Draenor\tranged").unwrap();
+        assert_eq!(tsv[0].name, "Thrall-Draenor");
+        assert_eq!(tsv[0].userId, "456");
+        assert_eq!(tsv[0].className, "Ranged");
+        assert_eq!(tsv[0].status, "primary");
+    }
+
+    #[test]
+    fn bad_rows_say_which_line() {
+        assert_eq!(parse_signups("Thrall,Tank\nJaina,Support").unwrap_err(), "Line 2: unknown role \"Support\", use tank, healer, melee, ranged or dps.");
+        assert!(parse_signups("Thrall\nthrall").unwrap_err().contains("Line 2: thrall is listed twice"));
+        assert_eq!(parse_signups("Thrall,Tank,,123\nJaina,Healer,,456\nThrallsalt,Melee,,123").unwrap_err(),
+            "Line 3: Discord ID 123 is already used by Thrall, list one character per person.");
+        assert!(parse_signups("\n# nothing\n").is_err());
+    }
+}
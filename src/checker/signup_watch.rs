@@ -0,0 +1,140 @@
+use std::{collections::HashMap, sync::mpsc::{self, Receiver}, time::Duration};
+
+use chrono::{Local, DateTime};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::{checker::{check_player::PlayerData, raid_sheet::{fetch_event, is_same_signup, Player}}, config::signup_filter::SignupFilter, http_client};
+
+// Keeps polling an event's sign-ups in the background. The UI compares each poll to the one before for the change log,
+// and to the last check's results to decide whether a recheck is needed.
+pub struct SignupWatch {
+    pub url: String,
+    // Newest first, time of the poll and what changed.
+    pub changes: Vec<(DateTime<Local>, String)>,
+    // Set when the sign-ups no longer match the checked players, cleared by whoever starts the recheck.
+    pub needs_check: bool,
+    signups: Option<Vec<Player>>,
+    receiver: Receiver<Vec<Player>>,
+    task: JoinHandle<()>,
+}
+
+impl SignupWatch {
+    pub fn start(url: String, interval_minutes: u64) -> Self {
+        info!("Watching {} every {} minutes", url, interval_minutes);
+        let (sender, receiver) = mpsc::channel();
+        let event_url = url.clone();
+        let task = http_client::runtime().spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_minutes.max(1) * 60));
+            loop {
+                interval.tick().await;
+                match fetch_event(&event_url).await {
+                    Ok(event) => {
+                        if sender.send(event.signUps).is_err() {
+                            return;
+                        }
+                    },
+                    Err(err) => error!("Watch of {} failed to poll: {}", event_url, err),
+                }
+            }
+        });
+
+        Self {
+            url,
+            changes: Vec::new(),
+            needs_check: false,
+            signups: None,
+            receiver,
+            task,
+        }
+    }
+
+    // Picks up the latest poll, if there is one.
+    pub fn poll(&mut self, players: &[PlayerData], filter: &SignupFilter) {
+        let Some(signups) = self.receiver.try_iter().last() else {
+            return;
+        };
+
+        // The first poll is only the starting point for the change log.
+        if let Some(previous) = &self.signups {
+            let now = Local::now();
+            for change in signup_changes(previous, &signups) {
+                info!("{}", change);
+                self.changes.insert(0, (now, change));
+            }
+        }
+
+        let viable: Vec<&Player> = signups.iter().filter(|x| filter.should_check(x)).collect();
+        if viable.len() != players.len() || viable.iter().any(|signup| !players.iter().any(|x| is_same_signup(x, signup))) {
+            self.needs_check = true;
+        }
+        self.signups = Some(signups);
+    }
+}
+
+impl Drop for SignupWatch {
+    fn drop(&mut self) {
+        info!("Stopped watching {}", self.url);
+        self.task.abort();
+    }
+}
+
+fn describe_status(player: &Player) -> String {
+    match player.className.to_lowercase().as_str() {
+        "bench" => format!("{} moved to bench", player.name),
+        "late" => format!("{} will be late", player.name),
+        "tentative" => format!("{} is tentative", player.name),
+        "absence" => format!("{} is absent", player.name),
+        _ => format!("{} signed as {}", player.name, player.specName.clone().unwrap_or(player.className.clone())),
+    }
+}
+
+// What changed between two polls of the same event, in sign-up order.
+pub fn signup_changes(before: &[Player], after: &[Player]) -> Vec<String> {
+    let previous: HashMap<&str, &Player> = before.iter().map(|x| (x.userId.as_str(), x)).collect();
+    let mut changes = Vec::new();
+
+    for player in after.iter() {
+        let Some(old) = previous.get(player.userId.as_str()) else {
+            changes.push(describe_status(player));
+            continue;
+        };
+
+        if old.name != player.name {
+            changes.push(format!("{} is now signed as {}", old.name, player.name));
+        }
+        if old.className != player.className || old.specName != player.specName || old.status != player.status {
+            changes.push(describe_status(player));
+        }
+    }
+
+    for player in before.iter().filter(|x| !after.iter().any(|y| y.userId == x.userId)) {
+        changes.push(format!("{} withdrew", player.name));
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signup(id: &str, class: &str, spec: &str) -> Player {
+        Player {
+            name: id.to_owned(),
+            userId: id.to_owned(),
+            className: class.to_owned(),
+            specName: Some(spec.to_owned()),
+            status: "primary".to_owned(),
+            ..Player::default()
+        }
+    }
+
+    #[test]
+    fn changes_between_polls() {
+        let before = vec![signup("Y", "Healer", "Holy"), signup("Z", "Tank", "Protection"), signup("W", "Melee", "Fury")];
+        let after = vec![signup("X", "Healer", "Holy"), signup("Y", "Bench", "Holy"), signup("W", "Melee", "Fury")];
+
+        assert_eq!(signup_changes(&before, &after), ["X signed as Holy", "Y moved to bench", "Z withdrew"]);
+        assert!(signup_changes(&after, &after).is_empty());
+    }
+}
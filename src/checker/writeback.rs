@@ -0,0 +1,110 @@
+use serde_json::json;
+use tracing::info;
+
+use crate::{checker::{raid_sheet::fetch_event, server_events::{api_url, event_url}}, config::settings::RaidHelperSettings, http_client};
+
+// Statuses a flagged sign-up can be moved to, raid-helper treats both as classes.
+pub static SIGNUP_STATUSES: [&str; 2] = ["Bench", "Tentative"];
+
+// A change to the event on raid-helper, only sent once the user confirmed the preview.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WritebackAction {
+    SetSignupClass { discord_id: String, name: String, class_name: String },
+    AppendNote(String),
+}
+
+impl WritebackAction {
+    pub fn describe(&self) -> String {
+        match self {
+            WritebackAction::SetSignupClass { name, class_name, .. } => format!("Move {} to {}", name, class_name),
+            WritebackAction::AppendNote(note) => format!("Add to the event description:\n{}", note),
+        }
+    }
+}
+
+async fn patch(settings: &RaidHelperSettings, url: String, body: serde_json::Value) -> Result<(), String> {
+    let client = http_client::client();
+    let request = client.patch(&url).header("Authorization", settings.api_key.trim()).json(&body);
+    let response = client.send(request).await.map_err(|err| format!("Request failed: {:?}", err))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Raid-helper returned {}: {}", status, text.trim()));
+    }
+    Ok(())
+}
+
+pub async fn apply(settings: &RaidHelperSettings, event_id: &str, action: &WritebackAction) -> Result<(), String> {
+    if settings.api_key.trim().is_empty() {
+        return Err("Changing an event needs the server's raid-helper API key.".to_owned());
+    }
+
+    info!("Raid-helper event {}: {}", event_id, action.describe());
+    match action {
+        WritebackAction::SetSignupClass { discord_id, class_name, .. } => {
+            patch(settings, format!("{}/v2/events/{}/signups/{}", api_url(settings), event_id, discord_id), json!({ "className": class_name })).await
+        },
+        WritebackAction::AppendNote(note) => {
+            // The description is replaced as a whole, so whatever's there now is kept in front of the note.
+            let event = fetch_event(&event_url(settings, event_id)).await?;
+            let description = if event.description.trim().is_empty() {
+                note.clone()
+            } else {
+                format!("{}\n\n{}", event.description.trim_end(), note)
+            };
+            patch(settings, format!("{}/v2/events/{}", api_url(settings), event_id), json!({ "description": description })).await
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::http_client::{block_on, test_server::{self, MockResponse}};
+
+    // Records every change it accepts, as "METHOD path body".
+    fn start_mock_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let address = test_server::start(move |request| {
+            if request.method == "PATCH" && !request.headers.iter().any(|x| x == "authorization: secret") {
+                return MockResponse::new("401 Unauthorized", "{}");
+            }
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/v4/events/42") => MockResponse::new("200 OK", r#"{"id":"42","name":"Raid","signUps":[],"description":"Bring flasks."}"#),
+                ("PATCH", "/v2/events/42") | ("PATCH", "/v2/events/42/signups/100") => {
+                    log.lock().unwrap().push(format!("{} {} {}", request.method, request.path, request.body));
+                    MockResponse::new("200 OK", "{}")
+                },
+                _ => MockResponse::new("404 Not Found", "{}"),
+            }
+        });
+        (address, received)
+    }
+
+    #[test]
+    fn moves_signup_and_appends_note() {
+        let (address, received) = start_mock_server();
+        let settings = RaidHelperSettings::for_test(address, "secret");
+
+        let bench = WritebackAction::SetSignupClass { discord_id: "100".to_owned(), name: "Tester".to_owned(), class_name: "Bench".to_owned() };
+        block_on(apply(&settings, "42", &bench)).unwrap();
+        block_on(apply(&settings, "42", &WritebackAction::AppendNote("Tester: Saved Kills".to_owned()))).unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0], r#"PATCH /v2/events/42/signups/100 {"className":"Bench"}"#);
+        assert_eq!(received[1], r#"PATCH /v2/events/42 {"description":"Bring flasks.\n\nTester: Saved Kills"}"#);
+    }
+
+    #[test]
+    fn rejected_or_missing_key_is_an_error() {
+        let (address, received) = start_mock_server();
+        let action = WritebackAction::AppendNote("note".to_owned());
+
+        assert!(block_on(apply(&RaidHelperSettings::for_test(address.clone(), ""), "42", &action)).unwrap_err().contains("API key"));
+        assert!(block_on(apply(&RaidHelperSettings::for_test(address, "wrong"), "42", &action)).unwrap_err().contains("401"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
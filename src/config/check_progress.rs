@@ -0,0 +1,200 @@
+use std::fs::{self, File};
+use std::path::Path;
+use std::io::{BufWriter, Write};
+use std::sync::{atomic::{AtomicU64, Ordering}, mpsc::{self, Sender}};
+use std::thread;
+
+use tracing::{error, info};
+
+use crate::checker::{check_player::PlayerData, raid_sheet::Player};
+
+pub static CHECK_PROGRESS_FILE: &str = "check_progress.json";
+
+// The sign-up check that's currently running. It's rewritten as players finish, so a check cut short by a crash
+// or the app closing can be resumed. Removed once the check finishes or is cancelled.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct CheckProgress {
+    pub raid_url: String,
+    pub raid_name: String,
+    pub players: Vec<PlayerData>,
+    // The full sign-up list of an imported check, there's no event to fetch it from again.
+    #[serde(default)]
+    pub imported: Option<Vec<Player>>,
+}
+
+impl CheckProgress {
+    pub fn new(raid_url: String, raid_name: String, players: Vec<PlayerData>) -> Self {
+        Self { raid_url, raid_name, players, imported: None }
+    }
+
+    // None when there's nothing to resume.
+    pub fn read<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<CheckProgress>(&content) {
+            Ok(progress) if !progress.raid_url.is_empty() => Some(progress),
+            Ok(_) => None,
+            Err(err) => {
+                error!("Error parsing check progress: {}. Ignoring it.", err);
+                None
+            }
+        }
+    }
+
+    // Written next to the file and moved over it, a crash mid-write leaves the last complete save.
+    pub fn save<P: AsRef<Path>>(&self, path: P) {
+        let temp = path.as_ref().with_extension("tmp");
+        let result = File::create(&temp)
+            .and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                serde_json::to_writer(&mut writer, self)?;
+                writer.flush()
+            })
+            .and_then(|_| fs::rename(&temp, &path));
+        if let Err(err) = result {
+            error!("Could not save check progress: {:?}", err);
+        }
+    }
+
+    pub fn clear<P: AsRef<Path>>(path: P) {
+        if fs::remove_file(&path).is_ok() {
+            info!("Removed check progress");
+        }
+    }
+}
+
+static NEXT_CHECK: AtomicU64 = AtomicU64::new(1);
+
+enum ProgressUpdate {
+    Started(u64, CheckProgress),
+    Checked(u64, Box<PlayerData>),
+    Clear(u64),
+}
+
+// Saves the progress of checks on its own thread, so a check never waits on the disk. Updates are written in the order
+// they're sent, anything a replaced or cancelled check still sends is dropped.
+#[derive(Clone)]
+pub struct ProgressWriter {
+    sender: Sender<ProgressUpdate>,
+    check: u64,
+}
+
+impl ProgressWriter {
+    pub fn spawn<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut current: Option<(u64, CheckProgress)> = None;
+            while let Ok(update) = receiver.recv() {
+                // Players that finished while the last save was written go into one save.
+                let mut changed = false;
+                for update in std::iter::once(update).chain(receiver.try_iter()) {
+                    match update {
+                        ProgressUpdate::Started(check, progress) => {
+                            current = Some((check, progress));
+                            changed = true;
+                        },
+                        ProgressUpdate::Checked(check, player) => {
+                            if let Some((_, progress)) = current.as_mut().filter(|x| x.0 == check) {
+                                progress.players.push(*player);
+                                changed = true;
+                            }
+                        },
+                        ProgressUpdate::Clear(check) => {
+                            if current.as_ref().is_some_and(|x| x.0 == check) {
+                                current = None;
+                                changed = false;
+                                CheckProgress::clear(&path);
+                            }
+                        },
+                    }
+                }
+                if let (true, Some((_, progress))) = (changed, &current) {
+                    progress.save(&path);
+                }
+            }
+        });
+        Self { sender, check: 0 }
+    }
+
+    // A writer for the next check, which takes over the file once it has started.
+    pub fn for_new_check(&self) -> Self {
+        Self { sender: self.sender.clone(), check: NEXT_CHECK.fetch_add(1, Ordering::Relaxed) }
+    }
+
+    pub fn started(&self, progress: CheckProgress) {
+        let _ = self.sender.send(ProgressUpdate::Started(self.check, progress));
+    }
+
+    pub fn checked(&self, player: PlayerData) {
+        let _ = self.sender.send(ProgressUpdate::Checked(self.check, Box::new(player)));
+    }
+
+    // Removes the file after everything sent before has been written, if this is still the check being saved.
+    pub fn clear(&self) {
+        let _ = self.sender.send(ProgressUpdate::Clear(self.check));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    // The writer saves in the background.
+    fn wait_for(done: impl Fn() -> bool) {
+        let start = Instant::now();
+        while !done() && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(done());
+    }
+
+    #[test]
+    fn progress_round_trips_and_clears() {
+        let path = std::env::temp_dir().join(format!("raidchecker_progress_{}.json", std::process::id()));
+        assert!(CheckProgress::read(&path).is_none());
+
+        CheckProgress::new("https://raid-helper.xyz/api/v2/events/1".to_owned(), "Raid".to_owned(), Vec::new()).save(&path);
+        let progress = CheckProgress::read(&path).unwrap();
+        assert_eq!(progress.raid_name, "Raid");
+        assert!(progress.players.is_empty());
+
+        CheckProgress::clear(&path);
+        assert!(CheckProgress::read(&path).is_none());
+    }
+
+    #[test]
+    fn empty_url_is_nothing_to_resume() {
+        let path = std::env::temp_dir().join(format!("raidchecker_progress_empty_{}.json", std::process::id()));
+        CheckProgress::default().save(&path);
+        assert!(CheckProgress::read(&path).is_none());
+        CheckProgress::clear(&path);
+    }
+
+    #[test]
+    fn writer_saves_the_current_check_only() {
+        let path = std::env::temp_dir().join(format!("raidchecker_progress_writer_{}.json", std::process::id()));
+        let writer = ProgressWriter::spawn(&path);
+
+        let first = writer.for_new_check();
+        first.started(CheckProgress::new("import:first".to_owned(), "First".to_owned(), Vec::new()));
+        first.checked(PlayerData::for_test("1"));
+        wait_for(|| CheckProgress::read(&path).is_some_and(|x| x.players.len() == 1));
+
+        // A check started over the first one, which still finishes a player after being replaced.
+        let second = writer.for_new_check();
+        second.started(CheckProgress::new("import:second".to_owned(), "Second".to_owned(), vec![PlayerData::for_test("2")]));
+        first.checked(PlayerData::for_test("3"));
+        first.clear();
+        second.checked(PlayerData::for_test("4"));
+        wait_for(|| CheckProgress::read(&path).is_some_and(|x| x.players.len() == 2));
+        let progress = CheckProgress::read(&path).unwrap();
+        assert_eq!(progress.raid_name, "Second");
+        assert_eq!(progress.players.iter().map(|x| x.discord_id.as_str()).collect::<Vec<_>>(), ["2", "4"]);
+        assert!(!path.with_extension("tmp").exists());
+
+        second.clear();
+        wait_for(|| !path.exists());
+    }
+}
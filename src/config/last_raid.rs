@@ -13,7 +13,9 @@ pub struct LastRaid {
     pub raid_id: String,
     pub raid_name: String,
     pub sheet_type: RaidSheetType,
-    pub players: Vec<PlayerData>
+    pub players: Vec<PlayerData>,
+    #[serde(default)]
+    pub start_time: Option<i64>
 }
 
 impl Default for LastRaid    {
@@ -23,7 +25,8 @@ impl Default for LastRaid    {
             raid_id: String::default(),
             raid_name: String::default(),
             sheet_type: RaidSheetType::Normal,
-            players: Vec::new()
+            players: Vec::new(),
+            start_time: None
         }
     }
 }
@@ -1,4 +1,12 @@
 pub mod expansion_config;
 pub mod settings;
 pub mod last_raid;
-pub mod realms;
\ No newline at end of file
+pub mod check_progress;
+pub mod signup_filter;
+pub mod realms;
+pub mod region;
+pub mod template_mapping;
+pub mod rules;
+pub mod requirements;
+pub mod waivers;
+pub mod readiness;
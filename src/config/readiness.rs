@@ -0,0 +1,37 @@
+// How much each part of a player's readiness counts towards their score, 0 leaves it out.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ReadinessWeights {
+    pub item_level: f32,
+    pub gear: f32, // Enchants, sockets and special items
+    pub tier: f32,
+    pub embellishments: f32,
+    pub kills: f32,
+    pub buffs: f32,
+}
+
+impl Default for ReadinessWeights {
+    fn default() -> Self {
+        Self {
+            item_level: 3.0,
+            gear: 2.0,
+            tier: 1.0,
+            embellishments: 1.0,
+            kills: 2.0,
+            buffs: 1.0,
+        }
+    }
+}
+
+impl ReadinessWeights {
+    pub fn as_array_mut(&mut self) -> [(&mut f32, &'static str); 6] {
+        [
+            (&mut self.item_level, "Item level"),
+            (&mut self.gear, "Enchants, sockets and special items"),
+            (&mut self.tier, "Tier pieces"),
+            (&mut self.embellishments, "Embellishments"),
+            (&mut self.kills, "Required kills"),
+            (&mut self.buffs, "Raid buff"),
+        ]
+    }
+}
@@ -1,5 +1,9 @@
-use std::fs::{File};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fs;
+
+use tracing::error;
+
+use crate::config::region::Region;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 struct RealmLinks {}
@@ -7,11 +11,41 @@ struct RealmLinks {}
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 struct RealmKey {}
 
+// The realm index has one name when it's fetched for a locale, or each locale's name when it isn't.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(untagged)]
+enum RealmName {
+    Single(String),
+    Localized(BTreeMap<String, String>),
+}
+
+impl RealmName {
+    fn english(&self) -> &str {
+        match self {
+            RealmName::Single(name) => name,
+            RealmName::Localized(names) => names.get("en_US").or(names.get("en_GB")).or(names.values().next()).map_or("", |x| x.as_str()),
+        }
+    }
+
+    fn all(&self) -> Vec<&str> {
+        match self {
+            RealmName::Single(name) => vec![name.as_str()],
+            RealmName::Localized(names) => names.values().map(|x| x.as_str()).collect(),
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct Realms {
     #[serde(skip_deserializing)]
     key: Option<RealmKey>,
-    pub name: String,
+    #[serde(rename = "name")]
+    localized: RealmName,
+    #[serde(skip)]
+    pub name: String, // English
+    #[serde(skip)]
+    pub names: Vec<String>, // Every locale's, so realms typed in Korean or Chinese are matched too
+    #[serde(default)]
     id: i32,
     pub slug: String
 }
@@ -20,21 +54,85 @@ pub struct Realms {
 pub struct RealmJson {
     #[serde(skip_deserializing)]
     _links: Option<RealmLinks>,
-    pub realms: Vec<Realms>
+    pub realms: Vec<Realms>,
+    #[serde(skip)]
+    pub region: Region
 }
 
 impl RealmJson {
-    pub fn new() -> Self {
-        if Path::new("realms.json").exists() == false {
-            panic!("Could not find realms.json file!")
+    // A realms file next to the exe is used over the built in list, so it can be updated without a new build.
+    pub fn new(region: Region) -> Self {
+        let file_name = region.realms_file();
+        let mut realms: RealmJson = match fs::read_to_string(file_name).map_err(|x| x.to_string()).and_then(|x| serde_json::from_str(&x).map_err(|x| x.to_string())) {
+            Ok(realms) => realms,
+            Err(err) => {
+                error!("Could not read {}: {}. Using the built in realm list.", file_name, err);
+                serde_json::from_str(Self::built_in(region)).expect("built in realm list is valid")
+            }
+        };
+        realms.region = region;
+        realms.normalise_names();
+        return realms;
+    }
+
+    // Names are matched without spaces or case, most locales share a realm's English name so those collapse into one.
+    fn normalise_names(&mut self) {
+        let normalise = |name: &str| name.replace(" ", "").to_lowercase();
+        for realm in self.realms.iter_mut() {
+            realm.name = normalise(realm.localized.english());
+            realm.names = realm.localized.all().into_iter().map(normalise).collect();
+            realm.names.sort_unstable();
+            realm.names.dedup();
         }
+    }
 
-        let file = File::open("realms.json").unwrap();
-        let mut realms: RealmJson = serde_json::from_reader(file).unwrap();
-        
-        for realm in realms.realms.iter_mut() {
-            realm.name = realm.name.replace(" ", "").to_lowercase();
+    fn built_in(region: Region) -> &'static str {
+        match region {
+            Region::EU => include_str!("../../realms.json"),
+            Region::US => include_str!("../../realms_us.json"),
+            Region::KR => include_str!("../../realms_kr.json"),
+            Region::TW => include_str!("../../realms_tw.json"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_region_has_english_realm_names() {
+        for region in Region::ALL {
+            let realms = RealmJson::new(region);
+            assert!(!realms.realms.is_empty());
+            assert!(realms.realms.iter().all(|x| !x.name.is_empty() && x.names.contains(&x.name)), "{}", region.as_str());
         }
-        return realms;
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn korean_and_taiwanese_realms_have_local_names() {
+        let kr = RealmJson::new(Region::KR);
+        let guldan = kr.realms.iter().find(|x| x.slug == "guldan").unwrap();
+        assert_eq!(guldan.name, "gul'dan");
+        assert!(guldan.names.contains(&"굴단".to_owned()));
+
+        let tw = RealmJson::new(Region::TW);
+        let world_tree = tw.realms.iter().find(|x| x.slug == "world-tree").unwrap();
+        assert_eq!(world_tree.names, ["worldtree", "世界之樹"]);
+    }
+
+    #[test]
+    fn every_region_has_a_built_in_realm_list() {
+        for region in Region::ALL {
+            let realms: RealmJson = serde_json::from_str(RealmJson::built_in(region)).unwrap();
+            assert!(!realms.realms.is_empty(), "{}", region.as_str());
+        }
+    }
+
+    #[test]
+    fn locale_names_are_listed_once() {
+        let mut realms: RealmJson = serde_json::from_str(r#"{"realms": [{"name": {"de_DE": "Khadgar", "en_GB": "Khadgar", "ru_RU": "Кадгар", "fr_FR": "Khadgar"}, "slug": "khadgar"}]}"#).unwrap();
+        realms.normalise_names();
+        assert_eq!(realms.realms[0].names, ["khadgar", "кадгар"]);
+    }
+}
@@ -0,0 +1,95 @@
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Region {
+    #[default]
+    EU,
+    US,
+    KR,
+    TW,
+}
+
+static ARMORY_HOST: &str = "https://worldofwarcraft.blizzard.com";
+
+impl Region {
+    pub const ALL: [Region; 4] = [Region::EU, Region::US, Region::KR, Region::TW];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Region::EU => "Europe",
+            Region::US => "Americas & Oceania",
+            Region::KR => "Korea",
+            Region::TW => "Taiwan",
+        }
+    }
+
+    // Region part of the armory path, i.e. /character/eu/...
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Region::EU => "eu",
+            Region::US => "us",
+            Region::KR => "kr",
+            Region::TW => "tw",
+        }
+    }
+
+    pub fn locale(&self) -> &'static str {
+        match self {
+            Region::EU => "en-gb",
+            Region::US => "en-us",
+            Region::KR => "ko-kr",
+            Region::TW => "zh-tw",
+        }
+    }
+
+    // realms.json is kept as the EU list so older installs keep working.
+    pub fn realms_file(&self) -> &'static str {
+        match self {
+            Region::EU => "realms.json",
+            Region::US => "realms_us.json",
+            Region::KR => "realms_kr.json",
+            Region::TW => "realms_tw.json",
+        }
+    }
+
+    pub fn armory_url(&self) -> String {
+        format!("{}/{}", ARMORY_HOST, self.locale())
+    }
+
+    pub fn character_url(&self, realm_slug: &str, name: &str) -> String {
+        format!("{}/character/{}/{}/{}/", self.armory_url(), self.slug(), realm_slug, name).to_lowercase()
+    }
+
+    pub fn search_url(&self, name: &str) -> String {
+        format!("{}/search?q={}", self.armory_url(), name)
+    }
+}
+
+// Strips the armory host and locale, leaving "character/<region>/<realm>/<name>" so the path can be reused for other sites.
+pub fn armory_character_path(url: &str) -> String {
+    match url.find("character/") {
+        Some(idx) => url[idx..].trim_end_matches('/').to_string(),
+        None => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_use_each_regions_locale_and_slug() {
+        let expected = [
+            (Region::EU, "https://worldofwarcraft.blizzard.com/en-gb/character/eu/tarren-mill/thrall/"),
+            (Region::US, "https://worldofwarcraft.blizzard.com/en-us/character/us/tarren-mill/thrall/"),
+            (Region::KR, "https://worldofwarcraft.blizzard.com/ko-kr/character/kr/tarren-mill/thrall/"),
+            (Region::TW, "https://worldofwarcraft.blizzard.com/zh-tw/character/tw/tarren-mill/thrall/"),
+        ];
+        for (region, url) in expected {
+            assert_eq!(region.character_url("Tarren-Mill", "Thrall"), url);
+            assert_eq!(armory_character_path(url), format!("character/{}/tarren-mill/thrall", region.slug()));
+            assert_eq!(region.search_url("Thrall"), format!("https://worldofwarcraft.blizzard.com/{}/search?q=Thrall", region.locale()));
+        }
+
+        assert_eq!(Region::KR.character_url("guldan", "호드전사"), "https://worldofwarcraft.blizzard.com/ko-kr/character/kr/guldan/호드전사/");
+        assert_eq!(Region::TW.search_url("聖騎士"), "https://worldofwarcraft.blizzard.com/zh-tw/search?q=聖騎士");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::config::settings::{Preset, Slots};
+
+// What an override applies to. A spec is more specific than a class, which is more specific than a role.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OverrideScope {
+    Role,
+    Class,
+    Spec,
+}
+
+impl OverrideScope {
+    pub const ALL: [OverrideScope; 3] = [OverrideScope::Role, OverrideScope::Class, OverrideScope::Spec];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverrideScope::Role => "Role",
+            OverrideScope::Class => "Class",
+            OverrideScope::Spec => "Spec",
+        }
+    }
+}
+
+// Requirements that differ from the preset's for some players, anything left as None is the preset's.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RequirementOverride {
+    pub scope: OverrideScope,
+    pub value: String, // e.g. "tank", "Paladin", "Holy" or "Holy Paladin"
+    #[serde(default)]
+    pub average_ilvl: Option<i32>,
+    #[serde(default)]
+    pub embelishments: Option<i32>,
+    #[serde(default)]
+    pub slots: Option<Slots>,
+}
+
+impl RequirementOverride {
+    pub fn new(scope: OverrideScope, value: &str) -> Self {
+        Self {
+            scope,
+            value: value.trim().to_owned(),
+            average_ilvl: None,
+            embelishments: None,
+            slots: None,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}: {}", self.scope.as_str(), self.value)
+    }
+
+    // Spec names are shared between classes, so "Holy Paladin" only matches holy paladins and is more specific than "Holy".
+    fn specificity(&self, role: &str, class: &str, spec: &str) -> Option<u8> {
+        let matches = |x: &str| !x.is_empty() && self.value.eq_ignore_ascii_case(x);
+        match self.scope {
+            OverrideScope::Role => matches(role).then_some(1),
+            OverrideScope::Class => matches(class).then_some(2),
+            OverrideScope::Spec if matches(spec) => Some(3),
+            OverrideScope::Spec => (!spec.is_empty() && matches(&format!("{} {}", spec, class))).then_some(4),
+        }
+    }
+}
+
+// The requirements a player is checked against.
+#[derive(Debug)]
+pub struct Requirements<'a> {
+    pub average_ilvl: i32,
+    pub embelishments: i32,
+    pub slots: &'a Slots,
+}
+
+impl Preset {
+    // Each requirement comes from the most specific override that sets it, or the preset. Ties go to the one listed first.
+    pub fn requirements_for(&self, role: &str, class: &str, spec: &str) -> Requirements<'_> {
+        let mut matching: Vec<(u8, &RequirementOverride)> = self.overrides.iter()
+            .filter_map(|x| x.specificity(role, class, spec).map(|specificity| (specificity, x)))
+            .collect();
+        matching.sort_by_key(|x| std::cmp::Reverse(x.0));
+
+        Requirements {
+            average_ilvl: matching.iter().find_map(|x| x.1.average_ilvl).unwrap_or(self.average_ilvl),
+            embelishments: matching.iter().find_map(|x| x.1.embelishments).unwrap_or(self.embelishments),
+            slots: matching.iter().find_map(|x| x.1.slots.as_ref()).unwrap_or(&self.slots),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_override_wins() {
+        let mut preset = Preset { average_ilvl: 640, embelishments: 2, ..Preset::default() };
+        let mut tanks = RequirementOverride::new(OverrideScope::Role, "tank");
+        tanks.average_ilvl = Some(650);
+        tanks.embelishments = Some(1);
+        let mut paladins = RequirementOverride::new(OverrideScope::Class, "Paladin");
+        paladins.average_ilvl = Some(655);
+        let mut holy = RequirementOverride::new(OverrideScope::Spec, "Holy");
+        holy.average_ilvl = Some(630);
+        let mut holy_paladins = RequirementOverride::new(OverrideScope::Spec, "Holy Paladin");
+        holy_paladins.slots = Some(preset.slots.clone());
+        preset.overrides = vec![tanks, paladins, holy, holy_paladins];
+
+        let dps = preset.requirements_for("melee", "Warrior", "Fury");
+        assert_eq!((dps.average_ilvl, dps.embelishments), (640, 2));
+        assert!(std::ptr::eq(dps.slots, &preset.slots));
+
+        // The class beats the role for item level, the role still sets embellishments.
+        let prot_paladin = preset.requirements_for("Tank", "paladin", "Protection");
+        assert_eq!((prot_paladin.average_ilvl, prot_paladin.embelishments), (655, 1));
+
+        let holy_priest = preset.requirements_for("healer", "Priest", "Holy");
+        assert_eq!(holy_priest.average_ilvl, 630);
+        assert!(std::ptr::eq(holy_priest.slots, &preset.slots));
+
+        let holy_paladin = preset.requirements_for("healer", "Paladin", "Holy");
+        assert_eq!(holy_paladin.average_ilvl, 630);
+        assert!(std::ptr::eq(holy_paladin.slots, preset.overrides[3].slots.as_ref().unwrap()));
+    }
+}
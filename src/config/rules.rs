@@ -0,0 +1,239 @@
+use crate::config::settings::PriorityChecks;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Blocker,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Blocker, Severity::Warning, Severity::Info];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Blocker => "Blocker",
+            Severity::Warning => "Warning",
+            Severity::Info => "Info",
+        }
+    }
+}
+
+// What a rule condition looks at on a checked player. Kills are counted against the preset's saved and required bosses.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleField {
+    ItemLevel,
+    Level,
+    TierCount,
+    Embellishments,
+    SavedKills,
+    MissingKills,
+    MissingBuffs,
+    BadEnchants,
+    MissingSockets,
+    MissingSpecialItems,
+    Role,
+    Class,
+    Spec,
+}
+
+impl RuleField {
+    pub const ALL: [RuleField; 13] = [
+        RuleField::ItemLevel,
+        RuleField::Level,
+        RuleField::TierCount,
+        RuleField::Embellishments,
+        RuleField::SavedKills,
+        RuleField::MissingKills,
+        RuleField::MissingBuffs,
+        RuleField::BadEnchants,
+        RuleField::MissingSockets,
+        RuleField::MissingSpecialItems,
+        RuleField::Role,
+        RuleField::Class,
+        RuleField::Spec,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleField::ItemLevel => "Item level",
+            RuleField::Level => "Level",
+            RuleField::TierCount => "Tier pieces",
+            RuleField::Embellishments => "Embellishments",
+            RuleField::SavedKills => "Saved boss kills",
+            RuleField::MissingKills => "Required bosses not killed",
+            RuleField::MissingBuffs => "Missing raid buffs",
+            RuleField::BadEnchants => "Enchant issues",
+            RuleField::MissingSockets => "Missing sockets",
+            RuleField::MissingSpecialItems => "Missing special items",
+            RuleField::Role => "Role",
+            RuleField::Class => "Class",
+            RuleField::Spec => "Spec",
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        matches!(self, RuleField::Role | RuleField::Class | RuleField::Spec)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleOp {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl RuleOp {
+    pub const ALL: [RuleOp; 6] = [RuleOp::Less, RuleOp::LessOrEqual, RuleOp::Equal, RuleOp::NotEqual, RuleOp::GreaterOrEqual, RuleOp::Greater];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleOp::Less => "<",
+            RuleOp::LessOrEqual => "<=",
+            RuleOp::Equal => "=",
+            RuleOp::NotEqual => "!=",
+            RuleOp::GreaterOrEqual => ">=",
+            RuleOp::Greater => ">",
+        }
+    }
+}
+
+// What a field is compared to. The preset's own requirements can be used so rules follow them when they're changed.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub enum RuleValue {
+    Number(i32),
+    Text(String),
+    RequiredItemLevel,
+    RequiredEmbellishments,
+}
+
+impl RuleValue {
+    pub fn kind_str(&self) -> &'static str {
+        match self {
+            RuleValue::Number(_) => "Number",
+            RuleValue::Text(_) => "Text",
+            RuleValue::RequiredItemLevel => "Required item level",
+            RuleValue::RequiredEmbellishments => "Required embellishments",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct RuleCondition {
+    pub field: RuleField,
+    pub op: RuleOp,
+    pub value: RuleValue,
+}
+
+impl RuleCondition {
+    pub fn new(field: RuleField, op: RuleOp, value: RuleValue) -> Self {
+        Self { field, op, value }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// A user-defined check. The preset's rules are looked at in order and the first one a player matches decides their colour.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub conditions: Vec<RuleCondition>,
+    #[serde(default)]
+    pub match_any: bool, // Any condition instead of all of them
+    pub severity: Severity,
+    pub colour: [u8; 4],
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            name: "New rule".to_owned(),
+            enabled: true,
+            conditions: vec![RuleCondition::new(RuleField::ItemLevel, RuleOp::Less, RuleValue::RequiredItemLevel)],
+            match_any: false,
+            severity: Severity::Warning,
+            colour: [0xFF, 0xFF, 0x0, 0xFF],
+            message: String::new(),
+        }
+    }
+}
+
+impl Rule {
+    fn new(name: &str, conditions: Vec<RuleCondition>, severity: Severity, colour: [u8; 4], message: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            enabled: true,
+            conditions,
+            match_any: false,
+            severity,
+            colour,
+            message: message.to_owned(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        if self.message.is_empty() {
+            &self.name
+        } else {
+            &self.message
+        }
+    }
+
+    // The rule a legacy check became, with the colour the preset had for it.
+    pub fn from_priority_check(check: &PriorityChecks, colour: Option<[u8; 4]>) -> Self {
+        let above_zero = |field| vec![RuleCondition::new(field, RuleOp::Greater, RuleValue::Number(0))];
+        let mut rule = match check {
+            PriorityChecks::SavedKills => Rule::new(check.as_str(), above_zero(RuleField::SavedKills), Severity::Blocker,
+                [0xFF, 0x0, 0x0, 0xFF], "Killed bosses this lockout that the raid needs them unsaved on."),
+            PriorityChecks::Ilvl => Rule::new(check.as_str(),
+                vec![RuleCondition::new(RuleField::ItemLevel, RuleOp::Less, RuleValue::RequiredItemLevel)], Severity::Blocker,
+                [0x8B, 0x0, 0x0, 0xFF], "Item level is below the requirement."),
+            PriorityChecks::Unkilled => Rule::new(check.as_str(), above_zero(RuleField::MissingKills), Severity::Warning,
+                [0xFF, 0xFF, 0x0, 0xFF], "Hasn't killed all the required bosses."),
+            PriorityChecks::Enchantments => Rule {
+                match_any: true,
+                ..Rule::new(check.as_str(), vec![
+                    RuleCondition::new(RuleField::BadEnchants, RuleOp::Greater, RuleValue::Number(0)),
+                    RuleCondition::new(RuleField::Embellishments, RuleOp::Less, RuleValue::RequiredEmbellishments),
+                ], Severity::Warning, [0x8B, 0x0, 0x0, 0xFF], "Missing enchants or embellishments.")
+            },
+            PriorityChecks::SpecialItem => Rule::new(check.as_str(), above_zero(RuleField::MissingSpecialItems), Severity::Warning,
+                [0x8B, 0x0, 0x0, 0xFF], "Missing this season's special item."),
+            PriorityChecks::BadSocket => Rule::new(check.as_str(), above_zero(RuleField::MissingSockets), Severity::Warning,
+                [0x8B, 0x0, 0x0, 0xFF], "Missing sockets."),
+            PriorityChecks::RaidBuff => Rule::new(check.as_str(), above_zero(RuleField::MissingBuffs), Severity::Info,
+                [0xFF, 0xA5, 0x0, 0xFF], "Missing raid buff."),
+            PriorityChecks::MissingTier => Rule::new(check.as_str(),
+                vec![RuleCondition::new(RuleField::TierCount, RuleOp::Less, RuleValue::Number(4))], Severity::Info,
+                [218, 0, 255, 255], "Has less than 4 tier pieces."),
+        };
+        if let Some(colour) = colour {
+            rule.colour = colour;
+        }
+        rule
+    }
+}
+
+// The checks Raid Checker always had, in their default priority.
+pub fn default_rules() -> Vec<Rule> {
+    [
+        PriorityChecks::SavedKills,
+        PriorityChecks::Ilvl,
+        PriorityChecks::Unkilled,
+        PriorityChecks::Enchantments,
+        PriorityChecks::SpecialItem,
+        PriorityChecks::BadSocket,
+        PriorityChecks::RaidBuff,
+        PriorityChecks::MissingTier,
+    ].iter().map(|x| Rule::from_priority_check(x, None)).collect()
+}
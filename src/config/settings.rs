@@ -2,6 +2,8 @@ use std::{collections::{BTreeMap, HashMap, hash_map}, fs::{self, File}, io::{sel
 
 use tracing::error;
 
+use crate::config::{region::Region, requirements::RequirementOverride, rules::{default_rules, Rule}, readiness::ReadinessWeights, signup_filter::SignupFilter, waivers::Waiver};
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct SlotSetting {
     pub require_slot: bool,
@@ -25,6 +27,7 @@ fn default_require_greater() -> bool {
     false
 }
 
+// The fixed checks presets had before rules, kept to migrate them.
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 pub enum PriorityChecks {
     SavedKills = 0,
@@ -52,6 +55,136 @@ impl PriorityChecks {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum DataSource {
+    #[default]
+    Armory,
+    Replay,
+    BlizzardApi,
+}
+
+impl DataSource {
+    pub const ALL: [DataSource; 3] = [DataSource::Armory, DataSource::Replay, DataSource::BlizzardApi];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataSource::Armory => "Armory (live)",
+            DataSource::Replay => "Replay recordings",
+            DataSource::BlizzardApi => "Blizzard Profile API",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BlizzardApiSettings {
+    pub client_id: String,
+    pub client_secret: String,
+    // Empty uses the regional https://<region>.api.blizzard.com host.
+    #[serde(default)]
+    pub api_url: String,
+    #[serde(default = "default_oauth_url")]
+    pub oauth_url: String,
+}
+
+// Lets events be picked from a Discord server's upcoming raid-helper events instead of pasting a URL.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct RaidHelperSettings {
+    pub server_id: String,
+    // Only needed for servers that don't share their events publicly, from /apikey in Discord.
+    pub api_key: String,
+    #[serde(default = "default_raid_helper_api_url")]
+    pub api_url: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct CacheSettings {
+    pub enabled: bool,
+    pub dir: String,
+    pub profile_ttl_minutes: u64,
+    pub achievements_ttl_minutes: u64,
+    pub reputation_ttl_minutes: u64,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: "cache".to_owned(),
+            profile_ttl_minutes: 10,
+            achievements_ttl_minutes: 60,
+            reputation_ttl_minutes: 60,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct HttpSettings {
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub max_retries: u32,
+    pub backoff_base_ms: u64,
+    // Per host, 0 disables the limiter.
+    pub requests_per_second: u32,
+    // Empty means no proxy.
+    pub proxy: String,
+    pub user_agent: String,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            connect_timeout_secs: 10,
+            max_retries: 4,
+            backoff_base_ms: 500,
+            requests_per_second: 5,
+            proxy: String::new(),
+            user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/137.0.0.0 Safari/537.36".to_owned(),
+        }
+    }
+}
+
+fn default_oauth_url() -> String {
+    "https://oauth.battle.net/token".to_owned()
+}
+
+fn default_raid_helper_api_url() -> String {
+    "https://raid-helper.xyz/api".to_owned()
+}
+
+impl Default for RaidHelperSettings {
+    fn default() -> Self {
+        Self {
+            server_id: String::new(),
+            api_key: String::new(),
+            api_url: default_raid_helper_api_url(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl RaidHelperSettings {
+    // A server pointed at a mock raid-helper API.
+    pub fn for_test(api_url: String, api_key: &str) -> Self {
+        Self {
+            server_id: "1234".to_owned(),
+            api_key: api_key.to_owned(),
+            api_url,
+        }
+    }
+}
+
+impl Default for BlizzardApiSettings {
+    fn default() -> Self {
+        Self {
+            client_id: String::new(),
+            client_secret: String::new(),
+            api_url: String::new(),
+            oauth_url: default_oauth_url(),
+        }
+    }
+}
+
 impl Default for SlotSetting {
     fn default() -> Self {
         Self {
@@ -177,17 +310,38 @@ pub struct Preset {
     pub required_raids: BTreeMap<i32, RequiredRaid>,
     pub slots: Slots,
     pub skip_colour: Option<[u8; 4]>,
+    // The fixed checks' colours and priority from before rules, only read to migrate older presets.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ilvl_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub saved_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub unkilled_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bad_gear_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bad_socket_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bad_special_item_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub missing_tier_colour: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub buff_colour: Option<[u8; 4]>,
     #[serde(default = "default_check_priority")]
     pub check_priority: Vec<PriorityChecks>,
     pub regulars: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub waivers: BTreeMap<String, Vec<Waiver>>, // Discord ID, the player's waivers
+    #[serde(default)]
+    pub region: Region,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub signup_filter: SignupFilter,
+    #[serde(default)]
+    pub overrides: Vec<RequirementOverride>, // Role, class and spec specific requirements
+    #[serde(default)]
+    pub readiness_weights: ReadinessWeights,
 }
 
 impl Default for Preset {
@@ -200,28 +354,57 @@ impl Default for Preset {
             required_raids: BTreeMap::new(),
             slots: Slots::default(),
             skip_colour: Some([0xFF, 0xFF, 0x0, 0xFF]),
-            ilvl_colour: Some([0x8B, 0x0, 0x0, 0xFF]),
-            saved_colour: Some([0xFF, 0x0, 0x0, 0xFF]),
-            unkilled_colour: Some([0xFF, 0xFF, 0x0, 0xFF]),
-            bad_gear_colour: Some([0x8B, 0x0, 0x0, 0xFF]),
-            bad_socket_colour: Some([0x8B, 0x0, 0x0, 0xFF]),
-            bad_special_item_colour: Some([0x8B, 0x0, 0x0, 0xFF]),
-            missing_tier_colour: Some([218, 0, 255, 255]),
-            buff_colour: Some([0xFF, 0xA5, 0x0, 0xFF]),
+            ilvl_colour: None,
+            saved_colour: None,
+            unkilled_colour: None,
+            bad_gear_colour: None,
+            bad_socket_colour: None,
+            bad_special_item_colour: None,
+            missing_tier_colour: None,
+            buff_colour: None,
             regulars: None,
-            check_priority: vec![
-                PriorityChecks::SavedKills,
-                PriorityChecks::Ilvl,
-                PriorityChecks::Unkilled,
-                PriorityChecks::Enchantments,
-                PriorityChecks::SpecialItem,
-                PriorityChecks::BadSocket,
-                PriorityChecks::RaidBuff,
-            ],
+            waivers: BTreeMap::new(),
+            region: Region::default(),
+            signup_filter: SignupFilter::default(),
+            check_priority: Vec::new(),
+            rules: default_rules(),
+            overrides: Vec::new(),
+            readiness_weights: ReadinessWeights::default(),
         }
     }
 }
 
+impl Preset {
+    // Presets from before rules get one rule per check they had, in the same order and colours.
+    // Once migrated the old checks are emptied, so a preset whose rules were all deleted stays that way.
+    fn migrate_rules(&mut self) {
+        if !self.rules.is_empty() || self.check_priority.is_empty() {
+            return;
+        }
+
+        for check in [PriorityChecks::BadSocket, PriorityChecks::SpecialItem, PriorityChecks::MissingTier] {
+            if !self.check_priority.contains(&check) {
+                self.check_priority.push(check);
+            }
+        }
+
+        self.rules = self.check_priority.iter().map(|check| {
+            let colour = match check {
+                PriorityChecks::SavedKills => self.saved_colour.take(),
+                PriorityChecks::Ilvl => self.ilvl_colour.take(),
+                PriorityChecks::Unkilled => self.unkilled_colour.take(),
+                PriorityChecks::Enchantments => self.bad_gear_colour.take(),
+                PriorityChecks::BadSocket => self.bad_socket_colour.take(),
+                PriorityChecks::SpecialItem => self.bad_special_item_colour.take(),
+                PriorityChecks::RaidBuff => self.buff_colour.take(),
+                PriorityChecks::MissingTier => self.missing_tier_colour.take(),
+            };
+            Rule::from_priority_check(check, colour)
+        }).collect();
+        self.check_priority.clear();
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct LegacySettings {
     pub average_ilvl: i32,
@@ -249,6 +432,27 @@ pub struct Settings {
     pub presets: HashMap<String, Preset>,
     pub last_preset: Option<String>,
 
+    #[serde(default)]
+    pub data_source: DataSource,
+    #[serde(default)]
+    pub record_responses: bool,
+    #[serde(default = "default_recordings_dir")]
+    pub recordings_dir: String,
+    #[serde(default)]
+    pub blizzard_api: BlizzardApiSettings,
+    #[serde(default)]
+    pub cache: CacheSettings,
+    #[serde(default)]
+    pub raid_helper: RaidHelperSettings,
+    #[serde(default)]
+    pub http: HttpSettings,
+    #[serde(default = "default_check_concurrency")]
+    pub check_concurrency: usize,
+    #[serde(default = "default_recheck_after_minutes")]
+    pub recheck_after_minutes: i64,
+    #[serde(default = "default_watch_interval_minutes")]
+    pub watch_interval_minutes: u64,
+
     #[serde(skip)]
     pub current_preset: Preset,
 
@@ -256,6 +460,22 @@ pub struct Settings {
     pub dirty_state: i32
 }
 
+fn default_check_concurrency() -> usize {
+    6
+}
+
+fn default_recheck_after_minutes() -> i64 {
+    30
+}
+
+fn default_watch_interval_minutes() -> u64 {
+    5
+}
+
+fn default_recordings_dir() -> String {
+    "recordings".to_owned()
+}
+
 fn default_saved() -> BTreeMap<i32, RequiredRaid> {
     BTreeMap::new()
 }
@@ -278,6 +498,16 @@ impl Default for Settings {
         let mut setting = Self {
             presets: HashMap::new(),
             last_preset: None,
+            data_source: DataSource::default(),
+            record_responses: false,
+            recordings_dir: default_recordings_dir(),
+            blizzard_api: BlizzardApiSettings::default(),
+            raid_helper: RaidHelperSettings::default(),
+            cache: CacheSettings::default(),
+            http: HttpSettings::default(),
+            check_concurrency: default_check_concurrency(),
+            recheck_after_minutes: default_recheck_after_minutes(),
+            watch_interval_minutes: default_watch_interval_minutes(),
             current_preset: Preset::default(),
             dirty_state: 0
         };
@@ -332,7 +562,13 @@ impl Settings {
                             missing_tier_colour: legacy_settings.missing_tier_colour,
                             buff_colour: legacy_settings.buff_colour,
                             check_priority: legacy_settings.check_priority,
-                            regulars: legacy_settings.regulars
+                            regulars: legacy_settings.regulars,
+                            waivers: BTreeMap::new(),
+                            region: Region::default(),
+                            signup_filter: SignupFilter::default(),
+                            rules: Vec::new(),
+                            overrides: Vec::new(),
+                            readiness_weights: ReadinessWeights::default(),
                         };
                         new_settings.presets.insert("Default".to_owned(), preset);
                         new_settings.last_preset = Some("Default".to_owned());
@@ -342,55 +578,14 @@ impl Settings {
                 }
             }.unwrap();
 
+            for preset in settings.presets.values_mut() {
+                preset.migrate_rules();
+            }
             settings.current_preset = settings.presets.get(settings.last_preset.as_ref().unwrap()).unwrap().clone();
 
             if settings.current_preset.skip_colour == None {
                 settings.current_preset.skip_colour = Some([0xFF, 0xFF, 0x0, 0xFF]);
             }
-
-            if settings.current_preset.ilvl_colour == None {
-                settings.current_preset.ilvl_colour = Some([0x8B, 0x0, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.saved_colour == None {
-                settings.current_preset.saved_colour = Some([0xFF, 0x0, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.unkilled_colour == None {
-                settings.current_preset.unkilled_colour = Some([0xFF, 0xFF, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.bad_gear_colour == None {
-                settings.current_preset.bad_gear_colour = Some([0x8B, 0x0, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.buff_colour == None {
-                settings.current_preset.buff_colour = Some([0xFF, 0xA5, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.bad_socket_colour == None {
-                settings.current_preset.bad_socket_colour = Some([0x8B, 0x0, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.bad_special_item_colour == None {
-                settings.current_preset.bad_special_item_colour = Some([0x8B, 0x0, 0x0, 0xFF]);
-            }
-
-            if settings.current_preset.missing_tier_colour == None {
-                settings.current_preset.missing_tier_colour = Some([218, 0, 255, 255]);
-            }
-
-            if settings.current_preset.check_priority.iter().find(|x| **x == PriorityChecks::BadSocket).is_none() {
-                settings.current_preset.check_priority.push(PriorityChecks::BadSocket);
-            }
-
-            if settings.current_preset.check_priority.iter().find(|x: &&PriorityChecks| **x == PriorityChecks::SpecialItem).is_none() {
-                settings.current_preset.check_priority.push(PriorityChecks::SpecialItem);
-            }
-
-            if settings.current_preset.check_priority.iter().find(|x: &&PriorityChecks| **x == PriorityChecks::MissingTier).is_none() {
-                settings.current_preset.check_priority.push(PriorityChecks::MissingTier);
-            }
             Ok(settings)
         } else {
             Self::create_default(path)
@@ -409,4 +604,29 @@ impl Settings {
         let mut file = File::create("config.json").unwrap();
         file.write_all(json.as_bytes()).unwrap();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_checks_become_rules() {
+        let mut json = serde_json::to_value(Preset::default()).unwrap();
+        json.as_object_mut().unwrap().remove("rules");
+        json["check_priority"] = serde_json::json!(["Ilvl", "SavedKills"]);
+        json["ilvl_colour"] = serde_json::json!([1, 2, 3, 255]);
+
+        let mut preset: Preset = serde_json::from_value(json).unwrap();
+        preset.migrate_rules();
+        let names: Vec<&str> = preset.rules.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, ["Bad Item Level", "Saved Kills", "Sockets Missing", "Missing Special Item", "Missing Tier"]);
+        assert_eq!(preset.rules[0].colour, [1, 2, 3, 255]);
+        assert!(preset.check_priority.is_empty());
+        assert!(!serde_json::to_string(&preset).unwrap().contains("ilvl_colour"));
+
+        // Deleting every rule doesn't bring the defaults back.
+        preset.rules.clear();
+        preset.migrate_rules();
+        assert!(preset.rules.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use crate::checker::raid_sheet::Player;
+
+// What happens to a sign-up with a given status, class or role. Ordered so the strictest one wins.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignupHandling {
+    Check,
+    Queued,
+    Ignore,
+}
+
+impl SignupHandling {
+    pub const ALL: [SignupHandling; 3] = [SignupHandling::Check, SignupHandling::Queued, SignupHandling::Ignore];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignupHandling::Check => "Check",
+            SignupHandling::Queued => "Check as queued",
+            SignupHandling::Ignore => "Ignore",
+        }
+    }
+}
+
+// Which raid-helper sign-ups are checked, per preset. Names are matched case-insensitively and stored lowercased.
+// A sign-up's class decides over its role, the role is only looked at for classes that aren't listed.
+// Anything not listed at all is ignored.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct SignupFilter {
+    pub statuses: BTreeMap<String, SignupHandling>,
+    pub classes: BTreeMap<String, SignupHandling>,
+    pub roles: BTreeMap<String, SignupHandling>,
+}
+
+fn entries(names: &[(&str, SignupHandling)]) -> BTreeMap<String, SignupHandling> {
+    names.iter().map(|x| (x.0.to_owned(), x.1)).collect()
+}
+
+impl Default for SignupFilter {
+    fn default() -> Self {
+        Self {
+            statuses: entries(&[("primary", SignupHandling::Check), ("queued", SignupHandling::Queued)]),
+            classes: entries(&[
+                ("tank", SignupHandling::Check),
+                ("healer", SignupHandling::Check),
+                ("melee", SignupHandling::Check),
+                ("ranged", SignupHandling::Check),
+                ("dps", SignupHandling::Check),
+                ("bench", SignupHandling::Queued),
+                ("tentative", SignupHandling::Ignore),
+            ]),
+            roles: entries(&[
+                ("tanks", SignupHandling::Check),
+                ("healers", SignupHandling::Check),
+                ("melee", SignupHandling::Check),
+                ("ranged", SignupHandling::Check),
+            ]),
+        }
+    }
+}
+
+impl SignupFilter {
+    pub fn handling(&self, player: &Player) -> SignupHandling {
+        let status = self.statuses.get(&player.status.to_lowercase()).copied().unwrap_or(SignupHandling::Ignore);
+        let class = self.classes.get(&player.className.to_lowercase()).copied()
+            .or_else(|| self.roles.get(&player.roleName.clone().unwrap_or_default().to_lowercase()).copied())
+            .unwrap_or(SignupHandling::Ignore);
+        status.max(class)
+    }
+
+    pub fn should_check(&self, player: &Player) -> bool {
+        self.handling(player) != SignupHandling::Ignore
+    }
+
+    pub fn is_queued(&self, player: &Player) -> bool {
+        self.handling(player) != SignupHandling::Check
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signup(status: &str, class: &str, role: Option<&str>) -> Player {
+        Player {
+            status: status.to_owned(),
+            className: class.to_owned(),
+            roleName: role.map(|x| x.to_owned()),
+            ..Player::default()
+        }
+    }
+
+    #[test]
+    fn default_matches_raid_helper_statuses() {
+        let filter = SignupFilter::default();
+        assert_eq!(filter.handling(&signup("primary", "Tank", None)), SignupHandling::Check);
+        assert_eq!(filter.handling(&signup("queued", "Healer", None)), SignupHandling::Queued);
+        assert_eq!(filter.handling(&signup("primary", "Bench", Some("Melee"))), SignupHandling::Queued);
+        assert_eq!(filter.handling(&signup("primary", "Tentative", Some("Tanks"))), SignupHandling::Ignore);
+        // Classes the filter doesn't know fall back to the role.
+        assert_eq!(filter.handling(&signup("primary", "Warrior", Some("Melee"))), SignupHandling::Check);
+        assert_eq!(filter.handling(&signup("primary", "Absence", None)), SignupHandling::Ignore);
+        assert_eq!(filter.handling(&signup("absence", "Tank", None)), SignupHandling::Ignore);
+    }
+
+    #[test]
+    fn custom_entries_are_used() {
+        let mut filter = SignupFilter::default();
+        filter.classes.insert("tentative".to_owned(), SignupHandling::Queued);
+        filter.roles.insert("support".to_owned(), SignupHandling::Check);
+
+        let tentative = signup("primary", "Tentative", None);
+        assert!(filter.should_check(&tentative));
+        assert!(filter.is_queued(&tentative));
+        assert!(!filter.is_queued(&signup("Primary", "Evoker", Some("Support"))));
+    }
+}
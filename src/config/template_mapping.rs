@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use tracing::error;
+
+use crate::checker::{check_player::PlayerData, raid_sheet::RaidSheetType};
+
+pub static TEMPLATES_FILE: &str = "templates.json";
+
+// How a raid-helper template's class and role names translate to tank/healer/melee/ranged and WoW classes.
+// Names are matched case-insensitively and stored lowercased.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct TemplateMapping {
+    pub sheet_type: RaidSheetType,
+    #[serde(default)]
+    pub roles: BTreeMap<String, String>,
+    #[serde(default)]
+    pub classes: BTreeMap<String, String>,
+}
+
+fn entries(names: &[(&str, &str)]) -> BTreeMap<String, String> {
+    names.iter().map(|x| (x.0.to_owned(), x.1.to_owned())).collect()
+}
+
+// Role names raid-helper uses on its default templates.
+fn default_roles() -> BTreeMap<String, String> {
+    entries(&[
+        ("tank", "tank"),
+        ("tanks", "tank"),
+        ("healer", "healer"),
+        ("healers", "healer"),
+        ("melee", "melee"),
+        ("ranged", "ranged"),
+    ])
+}
+
+impl Default for TemplateMapping {
+    fn default() -> Self {
+        Self {
+            sheet_type: RaidSheetType::Normal,
+            roles: default_roles(),
+            classes: BTreeMap::new(),
+        }
+    }
+}
+
+impl TemplateMapping {
+    // Hand-edited files can use any case, roles are also compared lowercased when the sign-ups are sorted.
+    fn normalise(&mut self) {
+        self.roles = self.roles.iter().map(|x| (x.0.to_lowercase(), x.1.to_lowercase())).collect();
+        self.classes = self.classes.iter().map(|x| (x.0.to_lowercase(), x.1.clone())).collect();
+    }
+
+    fn lookup<'a>(map: &'a BTreeMap<String, String>, names: &[&str]) -> Option<&'a String> {
+        names.iter().filter(|x| !x.is_empty()).find_map(|x| map.get(&x.to_lowercase()))
+    }
+
+    // Sets the player's role and WoW class from whatever they signed up as. Names the mapping doesn't know are left alone.
+    pub fn apply(&self, player: &mut PlayerData) {
+        let names = [player.role_name.as_str(), player.class_name.as_str(), player.spec_name.as_str()];
+        let role = Self::lookup(&self.roles, &names).cloned();
+        let class = Self::lookup(&self.classes, &names[1..]).cloned();
+        if let Some(role) = role {
+            player.role_name = role;
+        }
+        if let Some(class) = class {
+            player.wow_class = class;
+        }
+    }
+}
+
+// Template mappings by raid-helper template ID, templates that aren't listed use the default one.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct TemplatesConfig {
+    #[serde(default)]
+    pub default: TemplateMapping,
+    #[serde(default)]
+    pub templates: BTreeMap<String, TemplateMapping>,
+}
+
+// Classes each game version has, added to in expansion order.
+const CLASSIC_CLASSES: [&str; 9] = ["Warrior", "Paladin", "Hunter", "Rogue", "Priest", "Shaman", "Mage", "Warlock", "Druid"];
+const WOTLK_CLASSES: [&str; 1] = ["Death Knight"];
+const MOP_CLASSES: [&str; 1] = ["Monk"];
+const RETAIL_CLASSES: [&str; 2] = ["Demon Hunter", "Evoker"];
+
+// Tanks on the WoW templates can sign up with the class "Tank", the spec tells which class they are.
+const TANK_SPECS: [(&str, &str); 7] = [
+    ("protection", "Warrior"),
+    ("protection1", "Paladin"),
+    ("feral", "Druid"),
+    ("guardian", "Druid"),
+    ("blood", "Death Knight"),
+    ("brewmaster", "Monk"),
+    ("vengeance", "Demon Hunter"),
+];
+
+// Raid-helper writes class names both with and without spaces, e.g. "DeathKnight".
+fn class_mapping(class_lists: &[&[&str]]) -> TemplateMapping {
+    let wow_classes: Vec<&str> = class_lists.iter().flat_map(|x| x.iter().copied()).collect();
+    let mut classes = BTreeMap::new();
+    for class in &wow_classes {
+        classes.insert(class.to_lowercase(), class.to_string());
+        classes.insert(class.to_lowercase().replace(' ', ""), class.to_string());
+    }
+    for (spec, class) in TANK_SPECS {
+        if wow_classes.contains(&class) {
+            classes.insert(spec.to_owned(), class.to_owned());
+        }
+    }
+
+    TemplateMapping {
+        sheet_type: RaidSheetType::Classes,
+        roles: default_roles(),
+        classes,
+    }
+}
+
+impl Default for TemplatesConfig {
+    fn default() -> Self {
+        let classic: &[&[&str]] = &[&CLASSIC_CLASSES];
+        let wotlk: &[&[&str]] = &[&CLASSIC_CLASSES, &WOTLK_CLASSES];
+        let mop: &[&[&str]] = &[&CLASSIC_CLASSES, &WOTLK_CLASSES, &MOP_CLASSES];
+        let retail: &[&[&str]] = &[&CLASSIC_CLASSES, &WOTLK_CLASSES, &MOP_CLASSES, &RETAIL_CLASSES];
+
+        let templates = [
+            ("wowclassic1", classic),
+            ("wowsod1", classic),
+            ("wowtbc1", classic),
+            ("wowwotlk1", wotlk),
+            ("wowcata1", wotlk),
+            ("wowmop1", mop),
+            ("wowretail1", retail),
+        ];
+
+        Self {
+            default: TemplateMapping::default(),
+            templates: templates.into_iter().map(|x| (x.0.to_owned(), class_mapping(x.1))).collect(),
+        }
+    }
+}
+
+impl TemplatesConfig {
+    fn create_default<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let templates = TemplatesConfig::default();
+        let json = serde_json::to_string_pretty(&templates).unwrap();
+        let mut file = File::create(path)?;
+        file.write_all(json.as_bytes())?;
+        Ok(templates)
+    }
+
+    // Templates shipped with Raid Checker are added to the file if it doesn't have them, the user's own are kept as they are.
+    pub fn read_or_create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        if !path.as_ref().exists() {
+            return Self::create_default(path);
+        }
+
+        let content = fs::read_to_string(&path)?;
+        match serde_json::from_str::<TemplatesConfig>(&content) {
+            Ok(mut config) => {
+                config.default.normalise();
+                config.templates = config.templates.into_iter().map(|(id, mut mapping)| {
+                    mapping.normalise();
+                    (id.to_lowercase(), mapping)
+                }).collect();
+                for (id, mapping) in TemplatesConfig::default().templates {
+                    config.templates.entry(id).or_insert(mapping);
+                }
+                Ok(config)
+            },
+            Err(err) => {
+                error!("Error parsing template mappings: {}. Using the default ones.", err);
+                Ok(TemplatesConfig::default())
+            }
+        }
+    }
+
+    pub fn for_template(&self, template_id: Option<&str>) -> &TemplateMapping {
+        template_id
+            .and_then(|id| self.templates.get(&id.to_lowercase()))
+            .unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signup(class_name: &str, role_name: &str, spec_name: &str) -> PlayerData {
+        PlayerData {
+            class_name: class_name.to_owned(),
+            role_name: role_name.to_owned(),
+            spec_name: spec_name.to_owned(),
+            ..PlayerData::for_test("1")
+        }
+    }
+
+    #[test]
+    fn shipped_templates_map_roles_and_classes() {
+        let config = TemplatesConfig::default();
+        let retail = config.for_template(Some("wowretail1"));
+        assert_eq!(retail.sheet_type, RaidSheetType::Classes);
+
+        let mut player = signup("deathknight", "tanks", "Blood");
+        retail.apply(&mut player);
+        assert_eq!(player.role_name, "tank");
+        assert_eq!(player.wow_class, "Death Knight");
+        assert_eq!(player.class_name, "deathknight");
+
+        // Unknown templates fall back to the default mapping, which only knows roles.
+        let fallback = config.for_template(Some("custom"));
+        assert_eq!(fallback.sheet_type, RaidSheetType::Normal);
+        let mut player = signup("healer", "", "");
+        fallback.apply(&mut player);
+        assert_eq!(player.role_name, "healer");
+        assert!(player.wow_class.is_empty());
+    }
+
+    #[test]
+    fn classic_templates_only_know_their_classes() {
+        let config = TemplatesConfig::default();
+        let classic = config.for_template(Some("wowclassic1"));
+        assert_eq!(classic.sheet_type, RaidSheetType::Classes);
+
+        let mut player = signup("Tank", "Tanks", "Protection1");
+        classic.apply(&mut player);
+        assert_eq!(player.role_name, "tank");
+        assert_eq!(player.wow_class, "Paladin");
+
+        // No death knights before Wrath, but Wrath and Cataclysm have them.
+        let mut player = signup("DeathKnight", "Melee", "Frost");
+        classic.apply(&mut player);
+        assert!(player.wow_class.is_empty());
+        config.for_template(Some("WoWCata1")).apply(&mut player);
+        assert_eq!(player.wow_class, "Death Knight");
+
+        let mut player = signup("Tank", "Tanks", "Brewmaster");
+        config.for_template(Some("wowcata1")).apply(&mut player);
+        assert!(player.wow_class.is_empty());
+        config.for_template(Some("wowmop1")).apply(&mut player);
+        assert_eq!(player.wow_class, "Monk");
+    }
+
+    #[test]
+    fn custom_template_from_file() {
+        let path = std::env::temp_dir().join(format!("raidchecker_templates_{}.json", std::process::id()));
+        fs::write(&path, r#"{"templates":{"GuildTemplate":{"sheet_type":"Classes",
+            "roles":{"Beschützer":"Tank","HEILER":"healer"},"classes":{"Schild":"Warrior","licht":"Paladin"}}}}"#).unwrap();
+        let config = TemplatesConfig::read_or_create(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert!(config.templates.contains_key("wowretail1"));
+        let mapping = config.for_template(Some("guildtemplate"));
+        assert_eq!(mapping.sheet_type, RaidSheetType::Classes);
+        let mut player = signup("SCHILD", "Beschützer", "");
+        mapping.apply(&mut player);
+        assert_eq!(player.role_name, "tank");
+        assert_eq!(player.wow_class, "Warrior");
+
+        // A class name that's a role on this template.
+        let mut player = signup("heiler", "", "");
+        mapping.apply(&mut player);
+        assert_eq!(player.role_name, "healer");
+        assert!(player.wow_class.is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Local};
+
+// What a waiver lets through: everything a rule finds, or any gear issue on one slot.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum WaiverTarget {
+    Category(String), // Rule name
+    Slot(String),     // Gear slot as the armory names it, e.g. "finger"
+}
+
+impl WaiverTarget {
+    pub fn label(&self) -> String {
+        match self {
+            WaiverTarget::Category(category) => category.clone(),
+            WaiverTarget::Slot(slot) => format!("{} slot", slot),
+        }
+    }
+}
+
+// A known problem an officer has accepted for a player. Waived issues still show, but don't count against them.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct Waiver {
+    pub target: WaiverTarget,
+    #[serde(default)]
+    pub expires: Option<i64>, // Unix seconds, None until removed
+    #[serde(default)]
+    pub note: String,
+}
+
+impl Waiver {
+    // Whether it covers an event at the given time, so a waiver for this lockout still holds for a raid checked next week.
+    pub fn is_active(&self, at: i64) -> bool {
+        self.expires.is_none_or(|x| at < x)
+    }
+
+    pub fn description(&self) -> String {
+        let mut description = self.target.label();
+        if let Some(expires) = self.expires.and_then(|x| DateTime::from_timestamp(x, 0)) {
+            description += &format!(" until {}", expires.with_timezone(&Local).format("%A %d %b %H:%M"));
+        }
+        if !self.note.is_empty() {
+            description += &format!(" ({})", self.note);
+        }
+        description
+    }
+}
@@ -1,8 +1,9 @@
 use std::{fs, io::Write};
-use reqwest::blocking::get;
 use semver::Version;
 use tracing::{info, warn};
 
+use crate::http_client;
+
 #[derive(serde::Deserialize)]
 struct MiniJsonData {
     rhcu_version: String,
@@ -64,20 +65,21 @@ impl ExpansionUpdateChecker {
     fn fetch_remote_and_store(&mut self) -> bool {
         info!("Fetching remote expansions.json data...");
         let url = "https://github.com/Alasnkz/RaidChecker/raw/refs/heads/main/expansions.json";
-        match get(url) {
-            Ok(response) => {
-                if !response.status().is_success() || response.status() == 404 {
-                    warn!("Failed to fetch expansions.json from remote: {}", response.status());
+        let client = http_client::client();
+        let response = http_client::block_on(async {
+            let response = client.send(client.get(url)).await?;
+            let status = response.status();
+            response.bytes().await.map(|bytes| (status, bytes))
+        });
+        match response {
+            Ok((status, bytes)) => {
+                if !status.is_success() || status == 404 {
+                    warn!("Failed to fetch expansions.json from remote: {}", status);
                     return false;
                 }
-                match response.bytes() {
-                    Ok(bytes) => {
-                        info!("Successfully fetched remote expansions.json data.");
-                        self.remote_data = Some(bytes.to_vec());
-                        true
-                    }
-                    Err(_) => false,
-                }
+                info!("Successfully fetched remote expansions.json data.");
+                self.remote_data = Some(bytes.to_vec());
+                true
             },
             _ => false,
         }
@@ -100,19 +102,26 @@ impl ExpansionUpdateChecker {
             "https://api.github.com/repos/Alasnkz/RaidChecker/releases/latest",
         );
 
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .get(&url)
-            .header("User-Agent", "expansion-checker")
-            .send()
-            .ok().unwrap();
+        let client = http_client::client();
+        let response = http_client::block_on(async {
+            let response = client.send(client.get(&url).header("User-Agent", "expansion-checker")).await?;
+            let status = response.status();
+            response.text().await.map(|text| (status, text))
+        });
+        let (status, text) = match response {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Failed to reach GitHub for the latest release information: {:?}", err);
+                return (false, String::new());
+            }
+        };
     
-        if !response.status().is_success() {
-            warn!("Failed to fetch the latest release information from GitHub: {}", response.status());
+        if !status.is_success() {
+            warn!("Failed to fetch the latest release information from GitHub: {}", status);
             return (false, String::new());
         }
     
-        let data: Option<GitHubRelease> = response.text().ok().and_then(|text| serde_json::from_str(&text).ok());
+        let data: Option<GitHubRelease> = serde_json::from_str(&text).ok();
         if data.is_some() {
             let data = data.unwrap();
             info!("Current version: {}, Latest version: {}", RHCU_VERSION, data.tag_name);
@@ -0,0 +1,399 @@
+use std::{collections::HashMap, future::Future, sync::{Arc, Mutex, OnceLock, RwLock}, time::Duration};
+
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, Client, IntoUrl, Method, Proxy, RequestBuilder, Response, StatusCode, Url};
+use tokio::{runtime::Runtime, time::{sleep, sleep_until, Instant}};
+use tracing::{error, info, warn};
+
+use crate::config::settings::HttpSettings;
+
+// Never wait longer than this on a single Retry-After, a misbehaving server shouldn't hang a check.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+static CLIENT: RwLock<Option<Arc<HttpClient>>> = RwLock::new(None);
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+// Every check and request runs on this runtime, pooled connections are bound to it.
+pub fn runtime() -> &'static Runtime {
+    RUNTIME.get_or_init(|| Runtime::new().expect("Failed to build tokio runtime"))
+}
+
+// For the few callers that live on the UI thread or run at startup.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+// Rebuilds the shared client, call whenever the HTTP settings may have changed.
+pub fn configure(settings: &HttpSettings) {
+    *CLIENT.write().unwrap() = Some(Arc::new(HttpClient::new(settings)));
+}
+
+pub fn client() -> Arc<HttpClient> {
+    if let Some(client) = CLIENT.read().unwrap().as_ref() {
+        return client.clone();
+    }
+
+    let mut client = CLIENT.write().unwrap();
+    client.get_or_insert_with(|| Arc::new(HttpClient::new(&HttpSettings::default()))).clone()
+}
+
+pub struct HttpClient {
+    client: Client,
+    settings: HttpSettings,
+    // Earliest time the next request to each host may go out.
+    next_request: Mutex<HashMap<String, Instant>>,
+}
+
+fn host_key(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(url) => format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default()),
+        Err(_) => String::new(),
+    }
+}
+
+// A rate limited request wasn't handled, but a server error or a timeout may come after the server acted on it.
+// Only requests that can safely be sent twice are retried then, a POST or PATCH could otherwise be applied twice.
+fn should_retry(status: StatusCode, idempotent: bool) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || (status.is_server_error() && idempotent)
+}
+
+// A connection that couldn't be made never reached the server.
+fn should_retry_error(err: &reqwest::Error, idempotent: bool) -> bool {
+    err.is_connect() || (err.is_timeout() && idempotent)
+}
+
+// Retry-After is either a number of seconds or an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+            (date - Utc::now()).to_std().unwrap_or_default()
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+impl HttpClient {
+    pub fn new(settings: &HttpSettings) -> Self {
+        let mut builder = Client::builder()
+            .user_agent(settings.user_agent.clone())
+            .timeout(Duration::from_secs(settings.timeout_secs))
+            .connect_timeout(Duration::from_secs(settings.connect_timeout_secs));
+
+        if !settings.proxy.is_empty() {
+            match Proxy::all(&settings.proxy) {
+                Ok(proxy) => builder = builder.proxy(proxy),
+                Err(err) => error!("Invalid proxy {}: {:?}", settings.proxy, err),
+            }
+        }
+
+        Self {
+            client: builder.build().unwrap_or_else(|err| {
+                error!("Could not build HTTP client, falling back to defaults: {:?}", err);
+                Client::new()
+            }),
+            settings: settings.clone(),
+            next_request: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    pub fn patch<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.client.patch(url)
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        Duration::from_millis(self.settings.backoff_base_ms.saturating_mul(1 << attempt.min(10)))
+    }
+
+    // Reserves the next slot for the host and sleeps until it comes up. Even without a rate limit a 429 still holds the host back.
+    async fn wait_for_host(&self, host: &str) {
+        let interval = match self.settings.requests_per_second {
+            0 => Duration::ZERO,
+            rate => Duration::from_secs(1) / rate,
+        };
+        let now = Instant::now();
+        let slot = {
+            let mut next_request = self.next_request.lock().unwrap();
+            let next = next_request.entry(host.to_string()).or_insert(now);
+            let slot = (*next).max(now);
+            *next = slot + interval;
+            slot
+        };
+
+        if slot > now {
+            sleep_until(slot).await;
+        }
+    }
+
+    // A 429 applies to everyone talking to that host, not just the request that got it.
+    fn delay_host(&self, host: &str, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut next_request = self.next_request.lock().unwrap();
+        let next = next_request.entry(host.to_string()).or_insert(until);
+        *next = (*next).max(until);
+    }
+
+    // Sends the request, retrying connection failures, 429s and 5xx with exponential backoff.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let Some(this_request) = request.try_clone() else {
+                // Streaming bodies can't be replayed, send them once.
+                return request.send().await;
+            };
+
+            let built = this_request.try_clone().and_then(|x| x.build().ok());
+            let url = built.as_ref().map(|x| x.url().to_string()).unwrap_or_default();
+            let idempotent = built.as_ref().is_some_and(|x| x.method() == Method::GET || x.method() == Method::HEAD);
+            let host = host_key(&url);
+            self.wait_for_host(&host).await;
+
+            let last_attempt = attempt >= self.settings.max_retries;
+            match this_request.send().await {
+                Ok(response) if should_retry(response.status(), idempotent) && !last_attempt => {
+                    let delay = retry_after(&response).unwrap_or_else(|| self.backoff(attempt));
+                    warn!("{} returned {}, retrying in {:?} ({}/{})", url, response.status(), delay, attempt + 1, self.settings.max_retries);
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        self.delay_host(&host, delay);
+                    } else {
+                        sleep(delay).await;
+                    }
+                },
+                Err(err) if should_retry_error(&err, idempotent) && !last_attempt => {
+                    let delay = self.backoff(attempt);
+                    warn!("Request to {} failed ({:?}), retrying in {:?} ({}/{})", url, err, delay, attempt + 1, self.settings.max_retries);
+                    sleep(delay).await;
+                },
+                result => {
+                    if attempt > 0 {
+                        info!("{} finished after {} retries", url, attempt);
+                    }
+                    return result;
+                }
+            }
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_server {
+    use std::{io::{BufRead, BufReader, Read, Write}, net::TcpListener, thread};
+
+    pub struct MockRequest {
+        pub method: String,
+        pub path: String,
+        // Header lines, lowercased.
+        pub headers: Vec<String>,
+        pub body: String,
+    }
+
+    pub struct MockResponse {
+        pub status: &'static str,
+        pub headers: Vec<(&'static str, String)>,
+        pub body: String,
+    }
+
+    impl MockResponse {
+        pub fn new(status: &'static str, body: &str) -> Self {
+            Self { status, headers: Vec::new(), body: body.to_owned() }
+        }
+    }
+
+    // Minimal HTTP/1.1 server, returns the base URL it listens on.
+    pub fn start<F>(handler: F) -> String
+        where F: Fn(&MockRequest) -> MockResponse + Send + 'static
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut headers = Vec::new();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    let header = header.trim().to_lowercase();
+                    if let Some(length) = header.strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                    headers.push(header);
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or("/").split('?').next().unwrap().to_string();
+                let body = String::from_utf8_lossy(&body).to_string();
+                let response = handler(&MockRequest { method, path, headers, body });
+
+                let mut raw = format!("HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n", response.status, response.body.len());
+                for (name, value) in response.headers {
+                    raw += &format!("{}: {}\r\n", name, value);
+                }
+                raw += "\r\n";
+                raw += &response.body;
+                stream.write_all(raw.as_bytes()).unwrap();
+            }
+        });
+
+        address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::atomic::{AtomicUsize, Ordering}, time::Instant};
+
+    use super::{test_server::{start, MockResponse}, *};
+
+    fn settings() -> HttpSettings {
+        HttpSettings {
+            backoff_base_ms: 1,
+            requests_per_second: 0,
+            ..HttpSettings::default()
+        }
+    }
+
+    #[test]
+    fn retries_server_errors_until_success() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let address = start(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                MockResponse::new("503 Service Unavailable", "")
+            } else {
+                MockResponse::new("200 OK", "ok")
+            }
+        });
+
+        let client = HttpClient::new(&settings());
+        let response = block_on(client.send(client.get(&address))).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(block_on(response.text()).unwrap(), "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let address = start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            MockResponse::new("500 Internal Server Error", "")
+        });
+
+        let client = HttpClient::new(&HttpSettings { max_retries: 2, ..settings() });
+        let response = block_on(client.send(client.get(&address))).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let address = start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            MockResponse::new("404 Not Found", "")
+        });
+
+        let client = HttpClient::new(&settings());
+        assert_eq!(block_on(client.send(client.get(&address))).unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn server_errors_on_patch_are_not_resent() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let address = start(move |request| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            if request.method == "PATCH" {
+                MockResponse::new("500 Internal Server Error", "")
+            } else {
+                MockResponse::new("429 Too Many Requests", "")
+            }
+        });
+
+        let client = HttpClient::new(&HttpSettings { max_retries: 2, ..settings() });
+        let response = block_on(client.send(client.patch(&address).body("{}"))).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The rate limit is still waited out.
+        let response = block_on(client.send(client.post(&address).body("{}"))).unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn dropped_connections_are_not_retried() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                drop(stream);
+            }
+        });
+
+        let client = HttpClient::new(&settings());
+        let err = block_on(client.send(client.get(&address))).unwrap_err();
+        assert!(!should_retry_error(&err, true));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn honours_retry_after_on_429() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let address = start(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                MockResponse { status: "429 Too Many Requests", headers: vec![("Retry-After", "1".to_owned())], body: String::new() }
+            } else {
+                MockResponse::new("200 OK", "ok")
+            }
+        });
+
+        let client = HttpClient::new(&settings());
+        let start = Instant::now();
+        assert_eq!(block_on(client.send(client.get(&address))).unwrap().status(), StatusCode::OK);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn rate_limit_spaces_requests_per_host() {
+        let address = start(|_| MockResponse::new("200 OK", "ok"));
+        let client = HttpClient::new(&HttpSettings { requests_per_second: 10, ..settings() });
+
+        let start = Instant::now();
+        for _ in 0..4 {
+            block_on(client.send(client.get(&address))).unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs;
+
+use crate::checker::{raid_sheet::Player, signup_import::parse_signups};
+
+pub enum ImportAction {
+    Close,
+    Check(String, Vec<Player>), // Name of the import, its sign-ups
+}
+
+// Sign-ups that aren't on raid-helper, from a CSV/TSV file or a pasted list of names.
+pub struct ImportUi {
+    name: String,
+    path: String,
+    text: String,
+    load_error: Option<String>,
+}
+
+impl Default for ImportUi {
+    fn default() -> Self {
+        Self {
+            name: "Imported sign-ups".to_owned(),
+            path: String::new(),
+            text: String::new(),
+            load_error: None,
+        }
+    }
+}
+
+impl ImportUi {
+    pub fn render(&mut self, ctx: &egui::Context) -> Option<ImportAction> {
+        let mut action = None;
+        egui::Window::new("Import sign-ups")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("One sign-up per line as Name-Realm, optionally followed by role, spec and Discord ID separated by commas or tabs.");
+                ui.label("A header line naming the columns (Name, Role, Spec, Discord ID) lets them come in any order.");
+
+                egui::Grid::new("import_grid").show(ui, |ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut self.name).on_hover_text("Shown as the event name on the sign-up view.");
+                    ui.end_row();
+
+                    ui.label("File");
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.path);
+                        if ui.button("Load").on_hover_text("Reads a .csv, .tsv or .txt file into the list below.").clicked() {
+                            match fs::read_to_string(self.path.trim()) {
+                                Ok(text) => {
+                                    self.text = text;
+                                    self.load_error = None;
+                                },
+                                Err(err) => self.load_error = Some(format!("Could not read {}: {}", self.path.trim(), err)),
+                            }
+                        }
+                    });
+                    ui.end_row();
+                });
+
+                if let Some(err) = &self.load_error {
+                    ui.label(egui::RichText::new(err).color(egui::Color32::RED));
+                }
+
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    ui.add(egui::TextEdit::multiline(&mut self.text).desired_rows(10).hint_text("Thrall-Draenor,Tank,Protection"));
+                });
+
+                let signups = parse_signups(&self.text);
+                match &signups {
+                    Ok(signups) => ui.label(format!("{} sign-ups", signups.len())),
+                    Err(err) => ui.label(egui::RichText::new(err).color(egui::Color32::RED)),
+                };
+
+                ui.horizontal(|ui| {
+                    let can_check = signups.is_ok() && !self.name.trim().is_empty();
+                    if ui.add_enabled(can_check, egui::Button::new("Check")).clicked() {
+                        action = Some(ImportAction::Check(self.name.trim().to_owned(), signups.unwrap_or_default()));
+                    }
+                    if ui.button("Close").clicked() {
+                        action = Some(ImportAction::Close);
+                    }
+                });
+            });
+        action
+    }
+}
@@ -3,7 +3,7 @@
 
 use std::{fs::OpenOptions, io::{self, BufWriter}};
 
-use checker::{check_player::PlayerData, raid_questions::{QuestionState, RaidCheckQuestions}, raid_sheet::RaidSheet};
+use checker::{check_player::PlayerData, raid_questions::{QuestionState, RaidCheckQuestions}, raid_sheet::RaidSheet, signup_import::is_import_url, signup_watch::SignupWatch};
 use chrono::{DateTime, TimeZone, Utc};
 use config::{expansion_config::ExpansionsConfig, settings::Settings};
 use egui::{TopBottomPanel, Visuals, Window};
@@ -14,13 +14,16 @@ use signups_ui::SignUpsUI;
 pub mod signups_ui;
 pub mod expansion_update;
 pub mod settings_ui;
-use config::last_raid::LastRaid;
+pub mod writeback_ui;
+pub mod import_ui;
+pub mod http_client;
+use config::{check_progress::{CheckProgress, CHECK_PROGRESS_FILE}, last_raid::LastRaid};
 use tracing::{error, info, level_filters::LevelFilter};
 use tracing_subscriber::layer::Layer;
 use tracing_subscriber::{fmt, layer::SubscriberExt, Registry};
 use tracing_subscriber::EnvFilter;
 
-use crate::{checker::{check_player::slug_to_name, raid_sheet::{Player, PlayerOnlyCheckType}}, config::expansion_config::{ExpansionSeasons, Expansion}, expansion_update::ExpansionUpdateChecker};
+use crate::{checker::{check_player::slug_to_name, raid_sheet::{NeedsAttentionAction, Player, PlayerOnlyCheckType}}, config::expansion_config::{ExpansionSeasons, Expansion}, expansion_update::ExpansionUpdateChecker};
 
 static SHOULD_RECHECK_ALL: u8 = 1;
 static SHOULD_RECHECK_ATTENDANCE: u8 = 2;
@@ -88,6 +91,14 @@ struct RaidHelperCheckerApp {
     ask_update: bool,
     ask_update_changes: String,
 
+    // A sign-up check that didn't get to finish last time.
+    resume_check: Option<CheckProgress>,
+    signup_watch: Option<SignupWatch>,
+    writeback_ui: writeback_ui::WritebackUi,
+    draw_writeback: bool,
+    import_ui: import_ui::ImportUi,
+    draw_import: bool,
+
     cache: CommonMarkCache,
 }
 
@@ -103,7 +114,7 @@ impl Default for RaidHelperCheckerApp {
             raid_sheet: RaidSheet::default(),
             signup_ui: SignUpsUI::default(),
             last_raid: LastRaid::read_or_create("last_raid.json").unwrap(),
-            realms: config::realms::RealmJson::new(),
+            realms: config::realms::RealmJson::new(config::region::Region::default()),
             clear_target: false,
             checked_player: None,
             draw_player_check: false,
@@ -113,10 +124,18 @@ impl Default for RaidHelperCheckerApp {
             ask_json_update_changes: String::new(),
             ask_update: false,
             ask_update_changes: String::new(),
+            resume_check: CheckProgress::read(CHECK_PROGRESS_FILE),
+            signup_watch: None,
+            writeback_ui: writeback_ui::WritebackUi::default(),
+            draw_writeback: false,
+            import_ui: import_ui::ImportUi::default(),
+            draw_import: false,
             cache: CommonMarkCache::default(),
         };
         app.reload_data();
+        app.realms = config::realms::RealmJson::new(app.settings.current_preset.region);
         app.raid_sheet.init_from_last_raid(&app.last_raid);
+        http_client::configure(&app.settings.http);
 
         let (need_update, changes) = ExpansionUpdateChecker::need_app_update();
         if  need_update {
@@ -215,6 +234,55 @@ impl RaidHelperCheckerApp{
     }
 }
 
+impl RaidHelperCheckerApp {
+    fn draw_signup_watch(&mut self, ctx: &egui::Context) {
+        // Checking a different event ends the watch.
+        if self.signup_watch.as_ref().is_some_and(|x| !self.last_raid.raid_url.is_empty() && x.url != self.last_raid.raid_url) {
+            self.signup_watch = None;
+        }
+        let Some(watch) = self.signup_watch.as_mut() else {
+            return;
+        };
+
+        watch.poll(&self.last_raid.players, &self.settings.current_preset.signup_filter);
+        let idle = self.raid_sheet.state == checker::raid_sheet::RaidSheetState::None && self.raid_questions.state == QuestionState::None;
+        if watch.needs_check && idle {
+            info!("Sign-ups of {} changed, rechecking", self.last_raid.raid_name);
+            watch.needs_check = false;
+            self.raid_sheet.init(watch.url.clone(), PlayerOnlyCheckType::None, self.settings.clone(), self.expansions.clone(), self.realms.clone(), self.settings.current_preset.saved_raids.clone(), self.last_raid.clone());
+        }
+
+        let mut stop = false;
+        Window::new("Sign-up changes")
+            .show(ctx, |ui| {
+                ui.label(format!("Watching {}, checking for changes every {} minutes.", self.last_raid.raid_name, self.settings.watch_interval_minutes));
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    if watch.changes.is_empty() {
+                        ui.label("No changes yet.");
+                    }
+                    for (time, change) in watch.changes.iter() {
+                        ui.label(format!("{} {}", time.format("%H:%M"), change));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Clear").clicked() {
+                        watch.changes.clear();
+                    }
+                    if ui.button("Stop watching").clicked() {
+                        stop = true;
+                    }
+                });
+            });
+
+        if stop {
+            self.signup_watch = None;
+        } else {
+            // Polls arrive in the background, keep repainting so they get picked up without any input.
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+    }
+}
+
 impl eframe::App for RaidHelperCheckerApp {
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
         if self.win_title_change {
@@ -274,7 +342,41 @@ impl eframe::App for RaidHelperCheckerApp {
                     });
                 });
         }
+        else if let Some(progress) = &self.resume_check {
+            let mut resume = None;
+            Window::new("Resume check")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label(format!("The check of {} didn't finish, {} sign-ups were already checked.", progress.raid_name, progress.players.len()));
+                    ui.label("Resuming only checks the sign-ups that don't have a result yet.");
+                    ui.horizontal(|ui| {
+                        if ui.button("Resume").clicked() {
+                            resume = Some(true);
+                        }
+                        if ui.button("Discard").clicked() {
+                            resume = Some(false);
+                        }
+                    });
+                });
+
+            if let Some(resume) = resume {
+                let progress = self.resume_check.take().unwrap();
+                if resume {
+                    info!("Resuming check of {}", progress.raid_name);
+                    let url = self.raid_sheet.resume(progress);
+                    self.raid_sheet.init(url, PlayerOnlyCheckType::None, self.settings.clone(), self.expansions.clone(), self.realms.clone(), self.settings.current_preset.saved_raids.clone(), self.last_raid.clone());
+                } else {
+                    CheckProgress::clear(CHECK_PROGRESS_FILE);
+                }
+            }
+        }
         else {
+            if self.realms.region != self.settings.current_preset.region {
+                info!("Region changed to {}, reloading realms.", self.settings.current_preset.region.as_str());
+                self.realms = config::realms::RealmJson::new(self.settings.current_preset.region);
+            }
+
             TopBottomPanel::top("top_panel").show(ctx, |ui| {
                 ui.horizontal(|ui| {
 
@@ -284,6 +386,10 @@ impl eframe::App for RaidHelperCheckerApp {
                         self.raid_questions.player_only = PlayerOnlyCheckType::None;
                     }
 
+                    if ui.button("Import sign-ups").on_hover_text("Check sign-ups from a CSV/TSV file or a pasted list of names instead of raid-helper.").clicked() {
+                        self.draw_import = !self.draw_import;
+                    }
+
                     if ui.button("Check single character").clicked() {
                         self.draw_player_check = !self.draw_player_check;
                     }
@@ -291,11 +397,30 @@ impl eframe::App for RaidHelperCheckerApp {
                         self.draw_settings = !self.draw_settings;
                     }
 
+                    let needs_attention = self.raid_sheet.needs_attention_count();
+                    if needs_attention > 0 && ui.button(format!("Needs attention ({})", needs_attention)).clicked() {
+                        self.raid_sheet.show_needs_attention = !self.raid_sheet.show_needs_attention;
+                    }
+
+                    if !self.last_raid.raid_id.is_empty() && ui.button("Update raid-helper")
+                        .on_hover_text("Move flagged players to bench or tentative, or note their issues on the event.").clicked() {
+                        self.draw_writeback = !self.draw_writeback;
+                    }
+
+                    if self.signup_watch.is_some() {
+                        if ui.button("Stop watching").clicked() {
+                            self.signup_watch = None;
+                        }
+                    } else if !self.last_raid.raid_url.is_empty() && !is_import_url(&self.last_raid.raid_url) && ui.button("Watch event")
+                        .on_hover_text("Polls the event for sign-up changes and checks newcomers automatically.").clicked() {
+                        self.signup_watch = Some(SignupWatch::start(self.last_raid.raid_url.clone(), self.settings.watch_interval_minutes));
+                    }
+
                 });
             });
 
             let mut should_recheck: u8 = 0;
-            let recheck_player = self.signup_ui.draw_signups(ctx, &mut self.settings, &self.expansions, &mut self.raid_sheet.active_players, &mut self.raid_sheet.queued_players, self.raid_sheet.sheet_type.clone(), &mut should_recheck, &mut self.clear_target, &mut self.checked_player);
+            let recheck_player = self.signup_ui.draw_signups(ctx, &mut self.settings, &self.expansions, &mut self.raid_sheet.active_players, &mut self.raid_sheet.queued_players, self.raid_sheet.sheet_type.clone(), &mut should_recheck, &mut self.clear_target, &mut self.checked_player, self.raid_sheet.event_start);
             if recheck_player.is_some() {
                 let armory_url = recheck_player.as_ref().unwrap().armory_url.clone();
                 let parts: Vec<_> = armory_url.trim_end_matches('/').rsplitn(3, '/').collect();
@@ -323,6 +448,20 @@ impl eframe::App for RaidHelperCheckerApp {
                 self.raid_sheet.recheck_raid_plan(&mut self.last_raid);
             }
 
+            if self.draw_import {
+                match self.import_ui.render(ctx) {
+                    Some(import_ui::ImportAction::Check(name, signups)) => {
+                        info!("Checking {} imported sign-ups as {}", signups.len(), name);
+                        self.draw_import = false;
+                        let url = self.raid_sheet.import(&name, signups);
+                        self.raid_questions.state = QuestionState::AskSaved;
+                        let _ = self.raid_questions.ask_questions(ctx, &self.expansions, Some(url), Some(PlayerOnlyCheckType::None), &mut self.settings);
+                    },
+                    Some(import_ui::ImportAction::Close) => self.draw_import = false,
+                    None => {},
+                }
+            }
+
             if self.raid_questions.state != checker::raid_questions::QuestionState::None {
                 let ret = self.raid_questions.ask_questions(ctx, &self.expansions, None, None, &mut self.settings);
                 if ret.is_some() {
@@ -337,6 +476,21 @@ impl eframe::App for RaidHelperCheckerApp {
 
             if self.raid_sheet.state != checker::raid_sheet::RaidSheetState::None {
                 self.raid_sheet.draw(ctx, &mut self.last_raid, &mut self.clear_target, &mut self.checked_player);
+            } else if self.raid_sheet.show_needs_attention {
+                if let Some(action) = self.raid_sheet.draw_needs_attention(ctx, &mut self.last_raid) {
+                    let (url, player_only) = match action {
+                        NeedsAttentionAction::Resolve(discord_id, armory_url) => (armory_url, PlayerOnlyCheckType::ArmoryUrlFromSheet(discord_id)),
+                        NeedsAttentionAction::Search(discord_id, name) => (name, PlayerOnlyCheckType::PlayerFromSheet(discord_id)),
+                    };
+                    self.raid_sheet.init(url, player_only, self.settings.clone(), self.expansions.clone(), self.realms.clone(), self.settings.current_preset.saved_raids.clone(), self.last_raid.clone());
+                }
+            }
+
+            self.draw_signup_watch(ctx);
+
+            if self.draw_writeback {
+                let players: Vec<PlayerData> = self.raid_sheet.active_players.iter().chain(self.raid_sheet.queued_players.iter()).cloned().collect();
+                self.draw_writeback = !self.writeback_ui.render(ctx, &mut self.settings, &self.last_raid, &players);
             }
 
             if self.draw_settings == true {
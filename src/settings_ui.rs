@@ -3,20 +3,26 @@ use std::collections::BTreeMap;
 use egui::text_edit;
 use tracing::warn;
 
-use crate::config::{self, expansion_config::ExpansionRaid, settings::{RequiredRaid, RequiredRaidDifficulty}};
+use crate::{checker::response_cache::ResponseCache, config::{self, expansion_config::ExpansionRaid, readiness::ReadinessWeights, region::Region, requirements::{OverrideScope, RequirementOverride}, rules::{default_rules, Rule, RuleCondition, RuleField, RuleOp, RuleValue, Severity}, settings::{DataSource, RequiredRaid, RequiredRaidDifficulty, Slots}, signup_filter::SignupHandling}, http_client};
 
 pub(crate) struct SettingsUi {
     pub draw_item_requirements: bool,
     pub draw_raid_requirements: bool,
     pub draw_saved_checker: bool,
-    pub draw_priority: bool,
-    pub colour_settings: bool,
+    pub rule_settings: bool,
+    pub readiness_settings: bool,
     pub regular_settings: bool,
+    pub data_source_settings: bool,
+    pub signup_filter_settings: bool,
     pub current_raid_id: i32,
     pub current_raid_difficulty: i32,
     pub priority_name_str: String,
     pub priority_discord_str: String,
     pub preset_name_str: String,
+    pub filter_name_str: [String; 3], // Status, class and role being added
+    pub current_override: Option<usize>, // None for everyone's item requirements
+    pub override_scope: OverrideScope,
+    pub override_value_str: String,
 }
 
 impl SettingsUi {
@@ -25,15 +31,21 @@ impl SettingsUi {
             draw_item_requirements: false,
             draw_raid_requirements: false,
             draw_saved_checker: false,
-            draw_priority: false,
-            colour_settings: false,
+            rule_settings: false,
+            readiness_settings: false,
             regular_settings: false,
+            data_source_settings: false,
+            signup_filter_settings: false,
             current_raid_id: 0,
             current_raid_difficulty: 1,
 
             priority_name_str: String::default(),
             priority_discord_str: String::default(),
             preset_name_str: String::default(),
+            filter_name_str: Default::default(),
+            current_override: None,
+            override_scope: OverrideScope::Role,
+            override_value_str: String::default(),
         }
     }
 
@@ -65,6 +77,17 @@ impl SettingsUi {
                         settings.dirty_state = settings.dirty_state + 1;
                     }
 
+                    egui::ComboBox::from_label("Region")
+                        .selected_text(settings.current_preset.region.as_str())
+                        .show_ui(ui, |ui| {
+                            for region in Region::ALL {
+                                if ui.selectable_value(&mut settings.current_preset.region, region, region.as_str()).changed() {
+                                    settings.dirty_state = settings.dirty_state + 1;
+                                    settings.save_mut();
+                                }
+                            }
+                        }).response.on_hover_text("Which armory region characters in this preset are looked up in.");
+
                     if ui.button("Item Requirements").clicked() {
                         self.draw_item_requirements = !self.draw_item_requirements;
                         settings.save_mut();
@@ -77,18 +100,26 @@ impl SettingsUi {
                         self.draw_raid_requirements = !self.draw_raid_requirements;
                         settings.save_mut();
                     }
-                    if ui.button("Check priority").clicked() {
-                        self.draw_priority = !self.draw_priority;
+                    if ui.button("Rules").on_hover_text("The checks players are coloured by, in priority order.").clicked() {
+                        self.rule_settings = !self.rule_settings;
                         settings.save_mut();
                     }
-                    if ui.button("Modify colours").clicked() {
-                        self.colour_settings = !self.colour_settings;
+                    if ui.button("Readiness").on_hover_text("How much each part of a player's readiness score counts.").clicked() {
+                        self.readiness_settings = !self.readiness_settings;
                         settings.save_mut();
                     }
                     if ui.button("Raid Regulars").clicked() {
                         self.regular_settings = !self.regular_settings;
                         settings.save_mut();
                     }
+                    if ui.button("Sign-up filters").clicked() {
+                        self.signup_filter_settings = !self.signup_filter_settings;
+                        settings.save_mut();
+                    }
+                    if ui.button("Data source").clicked() {
+                        self.data_source_settings = !self.data_source_settings;
+                        settings.save_mut();
+                    }
 
                     ui.separator();
                     ui.horizontal(|ui| {
@@ -115,7 +146,7 @@ impl SettingsUi {
             });
 
         if self.draw_item_requirements {
-            if Self::draw_item_requirements_settings(ctx, settings, expansions) {
+            if self.draw_item_requirements_settings(ctx, settings, expansions) {
                 self.draw_item_requirements = false;
                 settings.save_mut();
             }
@@ -135,18 +166,14 @@ impl SettingsUi {
             }
         }
 
-        if self.draw_priority {
-            if Self::draw_check_priority(ctx, settings) {
-                self.draw_priority = false;
-                settings.save_mut();
-            }
+        if self.rule_settings && Self::draw_rule_settings(ctx, settings) {
+            self.rule_settings = false;
+            settings.save_mut();
         }
-        
-        if self.colour_settings {
-            if Self::draw_colour_settings(ctx, settings) {
-                self.colour_settings = false;
-                settings.save_mut();
-            }
+
+        if self.readiness_settings && Self::draw_readiness_settings(ctx, settings) {
+            self.readiness_settings = false;
+            settings.save_mut();
         }
 
         if self.regular_settings {
@@ -155,10 +182,21 @@ impl SettingsUi {
                 settings.save_mut();
             }
         }
+
+        if self.signup_filter_settings && self.draw_signup_filter_settings(ctx, settings) {
+            self.signup_filter_settings = false;
+            settings.save_mut();
+        }
+
+        if self.data_source_settings && Self::draw_data_source_settings(ctx, settings) {
+            self.data_source_settings = false;
+            settings.save_mut();
+            http_client::configure(&settings.http);
+        }
         close
     }
 
-    fn draw_item_requirements_settings(ctx: &eframe::egui::Context, settings: &mut config::settings::Settings, expansions: &config::expansion_config::ExpansionsConfig) -> bool {
+    fn draw_item_requirements_settings(&mut self, ctx: &eframe::egui::Context, settings: &mut config::settings::Settings, expansions: &config::expansion_config::ExpansionsConfig) -> bool {
         let mut close: bool = false;
         let latest_expansion = expansions.latest_expansion.clone().unwrap();
         let current_season = latest_expansion.latest_season.clone();
@@ -168,6 +206,11 @@ impl SettingsUi {
             max_ilvl = 1000;
         }
 
+        // The preset may have changed since the override was picked.
+        if self.current_override.is_some_and(|x| x >= settings.current_preset.overrides.len()) {
+            self.current_override = None;
+        }
+
         let mut changed = false;
 
         egui::Window::new("Raid Item Requirements")
@@ -175,122 +218,202 @@ impl SettingsUi {
             .resizable(false)
             .show(ctx, |ui| {
                 ui.vertical(|ui| {
-                    ui.add(egui::Slider::new(&mut settings.current_preset.average_ilvl, base_ilvl..=max_ilvl).text("Average item level required"));
-                    ui.add(egui::Slider::new(&mut settings.current_preset.embelishments, 0..=2).text("Embelishments required"));
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        for item in settings.current_preset.slots.as_array_mut().iter_mut() {
-                            let seasonal_item = current_season.as_ref().and_then(|s| {
-                                if !s.seasonal_slot_data.is_empty() {
-                                    return s.seasonal_slot_data.iter().find(|x| x.slot == item.1);
+                    let selected = self.current_override.map_or("Everyone".to_owned(), |x| settings.current_preset.overrides[x].label());
+                    egui::ComboBox::from_label("Requirements for")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.current_override, None, "Everyone");
+                            for (index, requirement_override) in settings.current_preset.overrides.iter().enumerate() {
+                                ui.selectable_value(&mut self.current_override, Some(index), requirement_override.label());
+                            }
+                        }).response.on_hover_text("Overrides apply to players of a role, class or spec. The most specific one wins, anything it doesn't set comes from everyone's requirements.");
+
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("override_scope")
+                            .selected_text(self.override_scope.as_str())
+                            .show_ui(ui, |ui| {
+                                for scope in OverrideScope::ALL {
+                                    ui.selectable_value(&mut self.override_scope, scope, scope.as_str());
                                 }
-                                None
                             });
+                        ui.add(egui::TextEdit::singleline(&mut self.override_value_str).hint_text("tank, Paladin, Holy Paladin...").desired_width(120.0))
+                            .on_hover_text("A role as the sign-ups are grouped (tank, healer, melee, ranged), a class, a spec, or a spec and class.");
+                        if ui.add_enabled(!self.override_value_str.trim().is_empty(), egui::Button::new("Add override")).clicked() {
+                            settings.current_preset.overrides.push(RequirementOverride::new(self.override_scope, &self.override_value_str));
+                            self.current_override = Some(settings.current_preset.overrides.len() - 1);
+                            self.override_value_str.clear();
+                            changed = true;
+                        }
+                    });
+                    ui.separator();
 
-                            let agnostic_item = expansions.agnostic_slot_data.iter().find(|x| x.slot == item.1);
-                            let proper_item = latest_expansion.slot_data.iter().find(|x| x.slot == item.1);
+                    let Some(index) = self.current_override else {
+                        changed = ui.add(egui::Slider::new(&mut settings.current_preset.average_ilvl, base_ilvl..=max_ilvl).text("Average item level required")).changed() || changed;
+                        changed = ui.add(egui::Slider::new(&mut settings.current_preset.embelishments, 0..=2).text("Embelishments required")).changed() || changed;
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            changed = Self::draw_slot_requirements(ui, &mut settings.current_preset.slots, expansions) || changed;
+                        });
+                        return;
+                    };
 
-                            let has_enchant = (proper_item.is_some() && !proper_item.unwrap().enchant_ids.is_empty()) || (seasonal_item.is_some() && !seasonal_item.unwrap().enchant_ids.is_empty()) ||
-                                (seasonal_item.is_some() && !seasonal_item.unwrap().lesser_enchant_ids.is_empty()) ||
-                                (agnostic_item.is_some() && !agnostic_item.unwrap().enchant_ids.is_empty());
+                    let preset = &mut settings.current_preset;
+                    let (preset_ilvl, preset_embelishments, preset_slots) = (preset.average_ilvl, preset.embelishments, preset.slots.clone());
+                    let requirement_override = &mut preset.overrides[index];
 
-                            let has_expansional_enchant = proper_item.is_some() && !proper_item.unwrap().enchant_ids.is_empty();
-                            let has_lesser_enchants = (proper_item.is_some() && !proper_item.unwrap().lesser_enchant_ids.is_empty()) || 
-                                (seasonal_item.is_some() && !seasonal_item.unwrap().lesser_enchant_ids.is_empty()) || 
-                                (agnostic_item.is_some() && !agnostic_item.unwrap().lesser_enchant_ids.is_empty());
+                    let mut own_ilvl = requirement_override.average_ilvl.is_some();
+                    if ui.checkbox(&mut own_ilvl, "Own item level requirement").changed() {
+                        requirement_override.average_ilvl = own_ilvl.then_some(preset_ilvl);
+                        changed = true;
+                    }
+                    if let Some(average_ilvl) = requirement_override.average_ilvl.as_mut() {
+                        changed = ui.add(egui::Slider::new(average_ilvl, base_ilvl..=max_ilvl).text("Average item level required")).changed() || changed;
+                    }
 
-                            let has_special_item = (proper_item.is_some() && !proper_item.unwrap().special_item_id.is_empty()) ||
-                                (seasonal_item.is_some() && !seasonal_item.unwrap().special_item_id.is_empty());
+                    let mut own_embelishments = requirement_override.embelishments.is_some();
+                    if ui.checkbox(&mut own_embelishments, "Own embelishment requirement").changed() {
+                        requirement_override.embelishments = own_embelishments.then_some(preset_embelishments);
+                        changed = true;
+                    }
+                    if let Some(embelishments) = requirement_override.embelishments.as_mut() {
+                        changed = ui.add(egui::Slider::new(embelishments, 0..=2).text("Embelishments required")).changed() || changed;
+                    }
 
-                            let has_socket = (proper_item.is_some() && proper_item.unwrap().has_socket) || 
-                                (seasonal_item.is_some() && seasonal_item.unwrap().has_socket);
+                    let mut own_slots = requirement_override.slots.is_some();
+                    if ui.checkbox(&mut own_slots, "Own slot requirements").on_hover_text("Starts from everyone's slot requirements.").changed() {
+                        requirement_override.slots = own_slots.then_some(preset_slots);
+                        changed = true;
+                    }
+                    if let Some(slots) = requirement_override.slots.as_mut() {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            changed = Self::draw_slot_requirements(ui, slots, expansions) || changed;
+                        });
+                    }
 
-                            let has_greater_socket_item = (proper_item.is_some() && !proper_item.unwrap().greater_socket_item.is_empty()) ||
-                                (seasonal_item.is_some() && !seasonal_item.unwrap().greater_socket_item.is_empty());
+                    if ui.button("Remove override").clicked() {
+                        preset.overrides.remove(index);
+                        self.current_override = None;
+                        changed = true;
+                    }
+                });
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
 
-                            let max_sockets = if seasonal_item.is_some() {
-                                seasonal_item.unwrap().max_sockets
-                            } else if proper_item.is_some() {
-                                proper_item.unwrap().max_sockets
-                            } else if agnostic_item.is_some() {
-                                agnostic_item.unwrap().max_sockets
-                            } else {
-                                0
-                            };
+        if changed {
+            settings.dirty_state = settings.dirty_state + 1;
+        }
+        
+        return close;
+    }
 
-                            if !has_enchant && !has_expansional_enchant && item.0.require_slot {
-                                warn!("{} has a enchantment requirement, but there are no enchantments associated with it, turning it off.", item.1);
-                                item.0.require_slot = false;
-                            }
+    // The enchant, socket and special item requirements of each slot that has any.
+    fn draw_slot_requirements(ui: &mut egui::Ui, slots: &mut Slots, expansions: &config::expansion_config::ExpansionsConfig) -> bool {
+        let latest_expansion = expansions.latest_expansion.clone().unwrap();
+        let current_season = latest_expansion.latest_season.clone();
+        let mut changed = false;
+        for item in slots.as_array_mut().iter_mut() {
+            let seasonal_item = current_season.as_ref().and_then(|s| {
+                if !s.seasonal_slot_data.is_empty() {
+                    return s.seasonal_slot_data.iter().find(|x| x.slot == item.1);
+                }
+                None
+            });
 
-                            if !has_enchant && !has_expansional_enchant && item.0.require_latest {
-                                warn!("{} has a latest enchantment requirement, but there are no enchantments associated with it, turning it off.", item.1);
-                                item.0.require_latest = false;
-                            }
+            let agnostic_item = expansions.agnostic_slot_data.iter().find(|x| x.slot == item.1);
+            let proper_item = latest_expansion.slot_data.iter().find(|x| x.slot == item.1);
 
+            let has_enchant = (proper_item.is_some() && !proper_item.unwrap().enchant_ids.is_empty()) || (seasonal_item.is_some() && !seasonal_item.unwrap().enchant_ids.is_empty()) ||
+                (seasonal_item.is_some() && !seasonal_item.unwrap().lesser_enchant_ids.is_empty()) ||
+                (agnostic_item.is_some() && !agnostic_item.unwrap().enchant_ids.is_empty());
 
-                            if !has_special_item && item.0.require_special_item {
-                                warn!("{} has a special item requirement, but there are no special items associated with it, turning it off.", item.1);
-                                item.0.require_special_item = false;
-                            }
+            let has_expansional_enchant = proper_item.is_some() && !proper_item.unwrap().enchant_ids.is_empty();
+            let has_lesser_enchants = (proper_item.is_some() && !proper_item.unwrap().lesser_enchant_ids.is_empty()) || 
+                (seasonal_item.is_some() && !seasonal_item.unwrap().lesser_enchant_ids.is_empty()) || 
+                (agnostic_item.is_some() && !agnostic_item.unwrap().lesser_enchant_ids.is_empty());
 
-                            if !has_socket && item.0.require_sockets > 0 {
-                                warn!("{} has a socket requirement, but the slot does not require sockets, turning it off.", item.1);
-                                item.0.require_sockets = 0;
-                            }
+            let has_special_item = (proper_item.is_some() && !proper_item.unwrap().special_item_id.is_empty()) ||
+                (seasonal_item.is_some() && !seasonal_item.unwrap().special_item_id.is_empty());
 
-                            if !has_greater_socket_item && item.0.require_greater_socket {
-                                warn!("{} has a greater socket item requirement, but there are no greater socket items associated with it, turning it off.", item.1);
-                                item.0.require_greater_socket = false;
-                            }
+            let has_socket = (proper_item.is_some() && proper_item.unwrap().has_socket) || 
+                (seasonal_item.is_some() && seasonal_item.unwrap().has_socket);
 
-                            if !has_lesser_enchants && item.0.require_greater {
-                                warn!("{} has a greater enchantment requirement, but there are no lesser enchantments associated with it, turning it off.", item.1);
-                                item.0.require_greater = false;
-                            }
+            let has_greater_socket_item = (proper_item.is_some() && !proper_item.unwrap().greater_socket_item.is_empty()) ||
+                (seasonal_item.is_some() && !seasonal_item.unwrap().greater_socket_item.is_empty());
 
-                            
-                            if !has_enchant && !has_expansional_enchant && !has_lesser_enchants && !has_special_item && !has_socket {
-                                continue; // Skip if no requirements
-                            }
+            let max_sockets = if seasonal_item.is_some() {
+                seasonal_item.unwrap().max_sockets
+            } else if proper_item.is_some() {
+                proper_item.unwrap().max_sockets
+            } else if agnostic_item.is_some() {
+                agnostic_item.unwrap().max_sockets
+            } else {
+                0
+            };
 
-                            ui.collapsing(item.1, |ui| {
-                                if has_enchant {
-                                    changed = changed || ui.checkbox(&mut item.0.require_slot, "Require enchantment in slot").changed();
-                                    changed = changed || ui.checkbox(&mut item.0.require_latest, "Require recent enchantment").on_hover_text("Checks to see if the enchantment is from the most recent patch (where applicable, if not it will check the latest expansion).").changed();
-                                }
+            if !has_enchant && !has_expansional_enchant && item.0.require_slot {
+                warn!("{} has a enchantment requirement, but there are no enchantments associated with it, turning it off.", item.1);
+                item.0.require_slot = false;
+            }
 
-                                if has_enchant && has_lesser_enchants {
-                                    changed = changed || ui.checkbox(&mut item.0.require_greater, "Require greater enchantment").on_hover_text("Checks to see if the enchantment is a greater version of the enchantment, notable only for corruptions (TWW S2).").changed();
-                                }
+            if !has_enchant && !has_expansional_enchant && item.0.require_latest {
+                warn!("{} has a latest enchantment requirement, but there are no enchantments associated with it, turning it off.", item.1);
+                item.0.require_latest = false;
+            }
 
-                                if has_special_item {
-                                    changed = changed || ui.checkbox(&mut item.0.require_special_item, "Require special item").on_hover_text("Require a special item i.e. DISC belt").changed();
-                                }
-                                
-                                if has_socket {
-                                    let mut click = false;
-                                    changed = changed || ui.add(egui::Slider::new(&mut item.0.require_sockets, 0..=max_sockets).text("Sockets required")).changed();
-                                    if has_greater_socket_item {
-                                        changed = changed || ui.checkbox(&mut item.0.require_greater_socket, "Require greater socket item").on_hover_text("Checks to see if the item has a \"greater\" gem/fibre socketed into it, notable for TWW S3 Reshii Wraps fibers.").changed();
-                                    }
-                                    changed = changed || ui.checkbox(&mut item.0.warn_if_socket_unfilled, "Warn if a socket is unfilled").on_hover_text("Warn if a socket is unfilled, no matter if they have met the socket requirement.").changed();
-                                    
-                                }
-                                
-                            });
-                        }
-                    });
-                });
-                if ui.button("Close").clicked() {
-                    close = true;
+
+            if !has_special_item && item.0.require_special_item {
+                warn!("{} has a special item requirement, but there are no special items associated with it, turning it off.", item.1);
+                item.0.require_special_item = false;
+            }
+
+            if !has_socket && item.0.require_sockets > 0 {
+                warn!("{} has a socket requirement, but the slot does not require sockets, turning it off.", item.1);
+                item.0.require_sockets = 0;
+            }
+
+            if !has_greater_socket_item && item.0.require_greater_socket {
+                warn!("{} has a greater socket item requirement, but there are no greater socket items associated with it, turning it off.", item.1);
+                item.0.require_greater_socket = false;
+            }
+
+            if !has_lesser_enchants && item.0.require_greater {
+                warn!("{} has a greater enchantment requirement, but there are no lesser enchantments associated with it, turning it off.", item.1);
+                item.0.require_greater = false;
+            }
+
+            
+            if !has_enchant && !has_expansional_enchant && !has_lesser_enchants && !has_special_item && !has_socket {
+                continue; // Skip if no requirements
+            }
+
+            ui.collapsing(item.1, |ui| {
+                if has_enchant {
+                    changed = changed || ui.checkbox(&mut item.0.require_slot, "Require enchantment in slot").changed();
+                    changed = changed || ui.checkbox(&mut item.0.require_latest, "Require recent enchantment").on_hover_text("Checks to see if the enchantment is from the most recent patch (where applicable, if not it will check the latest expansion).").changed();
                 }
-            });
 
-        if changed {
-            settings.dirty_state = settings.dirty_state + 1;
+                if has_enchant && has_lesser_enchants {
+                    changed = changed || ui.checkbox(&mut item.0.require_greater, "Require greater enchantment").on_hover_text("Checks to see if the enchantment is a greater version of the enchantment, notable only for corruptions (TWW S2).").changed();
+                }
+
+                if has_special_item {
+                    changed = changed || ui.checkbox(&mut item.0.require_special_item, "Require special item").on_hover_text("Require a special item i.e. DISC belt").changed();
+                }
+                
+                if has_socket {
+                    let mut click = false;
+                    changed = changed || ui.add(egui::Slider::new(&mut item.0.require_sockets, 0..=max_sockets).text("Sockets required")).changed();
+                    if has_greater_socket_item {
+                        changed = changed || ui.checkbox(&mut item.0.require_greater_socket, "Require greater socket item").on_hover_text("Checks to see if the item has a \"greater\" gem/fibre socketed into it, notable for TWW S3 Reshii Wraps fibers.").changed();
+                    }
+                    changed = changed || ui.checkbox(&mut item.0.warn_if_socket_unfilled, "Warn if a socket is unfilled").on_hover_text("Warn if a socket is unfilled, no matter if they have met the socket requirement.").changed();
+                    
+                }
+                
+            });
         }
-        
-        return close;
+        changed
     }
 
     fn draw_raid_requirements_settings(&mut self, ctx: &eframe::egui::Context, settings: &mut config::settings::Settings, expansion_config: &config::expansion_config::ExpansionsConfig) -> bool {
@@ -525,246 +648,269 @@ impl SettingsUi {
         return close;
     }
 
-    fn draw_check_priority(ctx: &eframe::egui::Context, settings: &mut config::settings::Settings) -> bool {
-        let mut close: bool = false;
-        egui::Window::new("Modify check priority")
-            .collapsible(false)
-            .resizable(false)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    let mut new_prio = settings.current_preset.check_priority.clone();
-                    egui::Grid::new("prio_grid").show(ui, |ui| {
-                        for prio in settings.current_preset.check_priority.iter() {
-                            ui.label(prio.as_str());
-                            // Buttons column
-                            ui.horizontal(|ui| {
-                                let index = new_prio.iter_mut().position(|x| x == prio).unwrap();
-                                
-                                if index > 0 && ui.button("up").clicked() {
-                                    new_prio.swap(index, index - 1);
-                                }
-                                if index < new_prio.len() - 1 && ui.button("down").clicked() {
-                                    new_prio.swap(index, index + 1);
-                                }
-                            });
-                            ui.end_row();
-                        }
-                    });
-                    settings.current_preset.check_priority = new_prio;
-                    ui.horizontal(|ui| {
-                        if ui.button("Close").clicked() {
-                            close = true;
+    fn draw_filter_entries(ui: &mut egui::Ui, id: &str, entries: &mut BTreeMap<String, SignupHandling>, new_name: &mut String) {
+        let mut remove = None;
+        egui::Grid::new(id).show(ui, |ui| {
+            for (name, handling) in entries.iter_mut() {
+                ui.label(name.as_str());
+                egui::ComboBox::from_id_salt(format!("{}_{}", id, name))
+                    .selected_text(handling.as_str())
+                    .show_ui(ui, |ui| {
+                        for option in SignupHandling::ALL {
+                            ui.selectable_value(handling, option, option.as_str());
                         }
                     });
-                    
-                });
-            });
-        return close;
+                if ui.button("Remove").clicked() {
+                    remove = Some(name.clone());
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(name) = remove {
+            entries.remove(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(new_name).hint_text("Name as on raid-helper...").desired_width(150.0));
+            if ui.button("Add").clicked() && !new_name.trim().is_empty() {
+                entries.entry(new_name.trim().to_lowercase()).or_insert(SignupHandling::Check);
+                new_name.clear();
+            }
+        });
     }
 
-    fn draw_colour_settings(ctx: &eframe::egui::Context, settings: &mut config::settings::Settings) -> bool {
+    fn draw_signup_filter_settings(&mut self, ctx: &eframe::egui::Context, settings: &mut config::settings::Settings) -> bool {
         let mut close: bool = false;
-        egui::Window::new("Colour settings")
+        egui::Window::new("Sign-up filters")
             .collapsible(false)
             .resizable(false)
             .show(ctx, |ui| {
-
+                ui.label("Which sign-ups are checked. A sign-up's class decides over its role, the role is only used for classes not listed here. Anything not listed is ignored.");
+                let filter = &mut settings.current_preset.signup_filter;
+                let [status_name, class_name, role_name] = &mut self.filter_name_str;
+
+                ui.separator();
+                ui.label("Statuses");
+                Self::draw_filter_entries(ui, "filter_statuses", &mut filter.statuses, status_name);
+                ui.separator();
+                ui.label("Classes").on_hover_text("Raid-helper's class column, which also holds Bench, Tentative, Late and Absence.");
+                Self::draw_filter_entries(ui, "filter_classes", &mut filter.classes, class_name);
+                ui.separator();
+                ui.label("Roles");
+                Self::draw_filter_entries(ui, "filter_roles", &mut filter.roles, role_name);
+
+                ui.separator();
                 ui.horizontal(|ui| {
-                    ui.label("Skip colour");
-                    let s_skip_colour = settings.current_preset.skip_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut skip_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_skip_colour[0] as f32 / 255.0,
-                        s_skip_colour[1] as f32 / 255.0,
-                        s_skip_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut skip_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.skip_colour = Some([
-                            (skip_colour[0] * 255.0).round() as u8,
-                            (skip_colour[1] * 255.0).round() as u8,
-                            (skip_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    if ui.button("Reset to defaults").clicked() {
+                        *filter = config::signup_filter::SignupFilter::default();
                     }
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.label("Bad item level colour");
-                    let s_ilvl_colour = settings.current_preset.ilvl_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut ilvl_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_ilvl_colour[0] as f32 / 255.0,
-                        s_ilvl_colour[1] as f32 / 255.0,
-                        s_ilvl_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut ilvl_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.ilvl_colour = Some([
-                            (ilvl_colour[0] * 255.0).round() as u8,
-                            (ilvl_colour[1] * 255.0).round() as u8,
-                            (ilvl_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    if ui.button("Close").clicked() {
+                        close = true;
                     }
                 });
+            });
+        close
+    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Saved kills colour");
-                    let s_saved_colour = settings.current_preset.saved_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut saved_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_saved_colour[0] as f32 / 255.0,
-                        s_saved_colour[1] as f32 / 255.0,
-                        s_saved_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut saved_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.saved_colour = Some([
-                            (saved_colour[0] * 255.0).round() as u8,
-                            (saved_colour[1] * 255.0).round() as u8,
-                            (saved_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
-                    }
-                });
+    // Colour picker for a preset colour, true when it was changed.
+    fn colour_button(ui: &mut egui::Ui, colour: &mut [u8; 4]) -> bool {
+        let mut rgba = egui::Rgba::from_rgba_unmultiplied(
+            colour[0] as f32 / 255.0,
+            colour[1] as f32 / 255.0,
+            colour[2] as f32 / 255.0,
+            1.0,
+        );
+
+        if egui::color_picker::color_edit_button_rgba(ui, &mut rgba, egui::color_picker::Alpha::Opaque).changed() {
+            *colour = [
+                (rgba[0] * 255.0).round() as u8,
+                (rgba[1] * 255.0).round() as u8,
+                (rgba[2] * 255.0).round() as u8,
+                255,
+            ];
+            return true;
+        }
+        false
+    }
+
+    fn draw_rule_conditions(ui: &mut egui::Ui, rule: &mut Rule) {
+        ui.horizontal(|ui| {
+            ui.label("Message");
+            ui.add(egui::TextEdit::singleline(&mut rule.message).hint_text(rule.name.clone()).desired_width(300.0));
+        });
+
+        egui::ComboBox::from_id_salt("rule_match")
+            .selected_text(if rule.match_any { "Any condition" } else { "All conditions" })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut rule.match_any, false, "All conditions");
+                ui.selectable_value(&mut rule.match_any, true, "Any condition");
+            });
+
+        let mut remove = None;
+        egui::Grid::new("rule_conditions").show(ui, |ui| {
+            for (index, condition) in rule.conditions.iter_mut().enumerate() {
+                egui::ComboBox::from_id_salt(("rule_field", index))
+                    .selected_text(condition.field.as_str())
+                    .show_ui(ui, |ui| {
+                        for field in RuleField::ALL {
+                            ui.selectable_value(&mut condition.field, field, field.as_str());
+                        }
+                    });
+
+                // Role, class and spec are compared as text, everything else as numbers.
+                let is_text = condition.field.is_text();
+                if is_text != matches!(condition.value, RuleValue::Text(_)) {
+                    condition.value = if is_text { RuleValue::Text(String::new()) } else { RuleValue::Number(0) };
+                    condition.op = RuleOp::Equal;
+                }
+
+                egui::ComboBox::from_id_salt(("rule_op", index))
+                    .selected_text(condition.op.as_str())
+                    .width(40.0)
+                    .show_ui(ui, |ui| {
+                        for op in RuleOp::ALL.into_iter().filter(|x| !is_text || matches!(x, RuleOp::Equal | RuleOp::NotEqual)) {
+                            ui.selectable_value(&mut condition.op, op, op.as_str());
+                        }
+                    });
 
                 ui.horizontal(|ui| {
-                    ui.label("Unkilled bosses colour");
-                    let s_unkilled_colour = settings.current_preset.unkilled_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut unkilled_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_unkilled_colour[0] as f32 / 255.0,
-                        s_unkilled_colour[1] as f32 / 255.0,
-                        s_unkilled_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut unkilled_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.unkilled_colour = Some([
-                            (unkilled_colour[0] * 255.0).round() as u8,
-                            (unkilled_colour[1] * 255.0).round() as u8,
-                            (unkilled_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    if let RuleValue::Text(text) = &mut condition.value {
+                        ui.add(egui::TextEdit::singleline(text).desired_width(100.0));
+                        return;
                     }
-                });
 
-                ui.horizontal(|ui| {
-                    ui.label("Bad gear colour");
-                    let s_bad_gear_colour = settings.current_preset.bad_gear_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut bad_gear_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_bad_gear_colour[0] as f32 / 255.0,
-                        s_bad_gear_colour[1] as f32 / 255.0,
-                        s_bad_gear_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut bad_gear_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.bad_gear_colour = Some([
-                            (bad_gear_colour[0] * 255.0).round() as u8,
-                            (bad_gear_colour[1] * 255.0).round() as u8,
-                            (bad_gear_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    egui::ComboBox::from_id_salt(("rule_value", index))
+                        .selected_text(condition.value.kind_str())
+                        .show_ui(ui, |ui| {
+                            for value in [RuleValue::Number(0), RuleValue::RequiredItemLevel, RuleValue::RequiredEmbellishments] {
+                                let selected = condition.value.kind_str() == value.kind_str();
+                                if ui.selectable_label(selected, value.kind_str()).clicked() && !selected {
+                                    condition.value = value;
+                                }
+                            }
+                        });
+                    if let RuleValue::Number(number) = &mut condition.value {
+                        ui.add(egui::DragValue::new(number));
                     }
                 });
 
+                if ui.button("Remove").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            rule.conditions.remove(index);
+        }
+
+        if ui.button("Add condition").clicked() {
+            rule.conditions.push(RuleCondition::new(RuleField::ItemLevel, RuleOp::Less, RuleValue::Number(0)));
+        }
+    }
+
+    fn draw_readiness_settings(ctx: &eframe::egui::Context, settings: &mut config::settings::Settings) -> bool {
+        let mut close: bool = false;
+        let weights = settings.current_preset.readiness_weights.clone();
+        egui::Window::new("Readiness")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Each player gets a readiness score out of 100, made up of the parts below. A part's weight is how much it counts compared to the others, 0 leaves it out.");
+                ui.label("Item level drops to nothing at 10 below the requirement, gear by the share of the enchants, sockets and special items the slots require that are missing, and raid buff halves with each missing level.");
+                for (weight, label) in settings.current_preset.readiness_weights.as_array_mut() {
+                    ui.add(egui::Slider::new(weight, 0.0..=5.0).step_by(0.5).text(label));
+                }
                 ui.horizontal(|ui| {
-                    ui.label("Buff colour");
-                    let s_buff_colour = settings.current_preset.buff_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut buff_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_buff_colour[0] as f32 / 255.0,
-                        s_buff_colour[1] as f32 / 255.0,
-                        s_buff_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut buff_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.buff_colour = Some([
-                            (buff_colour[0] * 255.0).round() as u8,
-                            (buff_colour[1] * 255.0).round() as u8,
-                            (buff_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    if ui.button("Reset to defaults").clicked() {
+                        settings.current_preset.readiness_weights = ReadinessWeights::default();
                     }
-                });
-                
-                ui.horizontal(|ui| {
-                    ui.label("Missing socket colour");
-                    let s_buff_colour = settings.current_preset.bad_socket_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut buff_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_buff_colour[0] as f32 / 255.0,
-                        s_buff_colour[1] as f32 / 255.0,
-                        s_buff_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut buff_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.bad_socket_colour = Some([
-                            (buff_colour[0] * 255.0).round() as u8,
-                            (buff_colour[1] * 255.0).round() as u8,
-                            (buff_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    if ui.button("Close").clicked() {
+                        close = true;
                     }
                 });
+            });
+
+        if settings.current_preset.readiness_weights != weights {
+            settings.dirty_state += 1;
+        }
+        close
+    }
 
+    fn draw_rule_settings(ctx: &eframe::egui::Context, settings: &mut config::settings::Settings) -> bool {
+        let mut close: bool = false;
+        let previous_rules = settings.current_preset.rules.clone();
+        egui::Window::new("Rules")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Players are coloured by the first rule they match, from the top down. A rule matches when all of its conditions do, unless it's set to any.");
                 ui.horizontal(|ui| {
-                    ui.label("Missing special item colour");
-                    let s_buff_colour = settings.current_preset.bad_special_item_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut buff_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_buff_colour[0] as f32 / 255.0,
-                        s_buff_colour[1] as f32 / 255.0,
-                        s_buff_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut buff_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.bad_special_item_colour = Some([
-                            (buff_colour[0] * 255.0).round() as u8,
-                            (buff_colour[1] * 255.0).round() as u8,
-                            (buff_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                    ui.label("Not checked colour");
+                    let mut skip_colour = settings.current_preset.skip_colour.unwrap_or([0xFF, 0xFF, 0x0, 0xFF]);
+                    if Self::colour_button(ui, &mut skip_colour) {
+                        settings.current_preset.skip_colour = Some(skip_colour);
                     }
                 });
-
-                ui.horizontal(|ui| {
-                    ui.label("Missing tier colour");
-                    let s_buff_colour = settings.current_preset.missing_tier_colour.unwrap_or([255, 0, 0, 255]);
-                    let mut buff_colour = egui::Rgba::from_rgba_unmultiplied(
-                        s_buff_colour[0] as f32 / 255.0,
-                        s_buff_colour[1] as f32 / 255.0,
-                        s_buff_colour[2] as f32 / 255.0,
-                        1.0,
-                    );
-                
-                    if egui::color_picker::color_edit_button_rgba(ui, &mut buff_colour, egui::color_picker::Alpha::Opaque).changed() {
-                        settings.current_preset.missing_tier_colour = Some([
-                            (buff_colour[0] * 255.0).round() as u8,
-                            (buff_colour[1] * 255.0).round() as u8,
-                            (buff_colour[2] * 255.0).round() as u8,
-                            255,
-                        ]);
-                        settings.dirty_state = settings.dirty_state + 1;
+                ui.separator();
+
+                let rules = &mut settings.current_preset.rules;
+                let count = rules.len();
+                let mut swap = None;
+                let mut remove = None;
+                egui::ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                    for (index, rule) in rules.iter_mut().enumerate() {
+                        ui.push_id(index, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut rule.enabled, "").on_hover_text("Disabled rules are skipped.");
+                                Self::colour_button(ui, &mut rule.colour);
+                                ui.add(egui::TextEdit::singleline(&mut rule.name).desired_width(180.0));
+                                egui::ComboBox::from_id_salt("rule_severity")
+                                    .selected_text(rule.severity.as_str())
+                                    .show_ui(ui, |ui| {
+                                        for severity in Severity::ALL {
+                                            ui.selectable_value(&mut rule.severity, severity, severity.as_str());
+                                        }
+                                    });
+                                if index > 0 && ui.button("up").clicked() {
+                                    swap = Some((index, index - 1));
+                                }
+                                if index + 1 < count && ui.button("down").clicked() {
+                                    swap = Some((index, index + 1));
+                                }
+                                if ui.button("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                            ui.collapsing("Conditions", |ui| Self::draw_rule_conditions(ui, rule));
+                        });
                     }
                 });
+                if let Some((a, b)) = swap {
+                    rules.swap(a, b);
+                }
+                if let Some(index) = remove {
+                    rules.remove(index);
+                }
 
+                ui.separator();
                 ui.horizontal(|ui| {
+                    if ui.button("Add rule").clicked() {
+                        rules.push(Rule::default());
+                    }
+                    if ui.button("Reset to defaults").clicked() {
+                        *rules = default_rules();
+                    }
                     if ui.button("Close").clicked() {
                         close = true;
                     }
                 });
             });
 
+        // The sign-up list keeps each player's issues until the settings change.
+        if settings.current_preset.rules != previous_rules {
+            settings.dirty_state += 1;
+        }
         close
     }
 
@@ -821,4 +967,138 @@ impl SettingsUi {
             });
         close
     }
-}
\ No newline at end of file
+
+    fn draw_data_source_settings(ctx: &eframe::egui::Context, settings: &mut config::settings::Settings) -> bool {
+        let mut close: bool = false;
+        egui::Window::new("Data source")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.label("Where character data is read from. Replay reads pages saved by a previous run with recording enabled, so a check can be repeated without touching the armory. The Blizzard Profile API avoids scraping armory pages, but needs a Battle.net API client.");
+                    ui.separator();
+
+                    egui::ComboBox::from_label("Source")
+                        .selected_text(settings.data_source.as_str())
+                        .show_ui(ui, |ui| {
+                            for source in DataSource::ALL {
+                                ui.selectable_value(&mut settings.data_source, source, source.as_str());
+                            }
+                        });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Recordings directory");
+                        ui.add(egui::TextEdit::singleline(&mut settings.recordings_dir).desired_width(200.0));
+                    });
+
+                    if settings.data_source == DataSource::Armory {
+                        ui.checkbox(&mut settings.record_responses, "Record responses")
+                            .on_hover_text("Saves every armory page fetched into the recordings directory.");
+                    }
+
+                    if settings.data_source == DataSource::BlizzardApi {
+                        ui.separator();
+                        ui.label("Create a client at https://develop.battle.net/access/clients and paste its credentials here.");
+                        egui::Grid::new("blizzard_api_grid").show(ui, |ui| {
+                            ui.label("Client ID");
+                            ui.add(egui::TextEdit::singleline(&mut settings.blizzard_api.client_id).desired_width(250.0));
+                            ui.end_row();
+
+                            ui.label("Client secret");
+                            ui.add(egui::TextEdit::singleline(&mut settings.blizzard_api.client_secret).password(true).desired_width(250.0));
+                            ui.end_row();
+
+                            ui.label("API URL");
+                            ui.add(egui::TextEdit::singleline(&mut settings.blizzard_api.api_url).hint_text("Regional default").desired_width(250.0));
+                            ui.end_row();
+
+                            ui.label("OAuth URL");
+                            ui.add(egui::TextEdit::singleline(&mut settings.blizzard_api.oauth_url).desired_width(250.0));
+                            ui.end_row();
+                        });
+                    }
+
+                    ui.separator();
+                    ui.checkbox(&mut settings.cache.enabled, "Cache responses")
+                        .on_hover_text("Keeps fetched pages on disk so checking the same sign-up again shortly after doesn't fetch everything again. The Recheck button on a player always fetches fresh data.");
+                    if settings.cache.enabled {
+                        egui::Grid::new("cache_grid").show(ui, |ui| {
+                            ui.label("Cache directory");
+                            ui.add(egui::TextEdit::singleline(&mut settings.cache.dir).desired_width(200.0));
+                            ui.end_row();
+
+                            ui.label("Profile TTL (minutes)");
+                            ui.add(egui::DragValue::new(&mut settings.cache.profile_ttl_minutes).range(0..=10080));
+                            ui.end_row();
+
+                            ui.label("Achievements TTL (minutes)");
+                            ui.add(egui::DragValue::new(&mut settings.cache.achievements_ttl_minutes).range(0..=10080));
+                            ui.end_row();
+
+                            ui.label("Reputation TTL (minutes)");
+                            ui.add(egui::DragValue::new(&mut settings.cache.reputation_ttl_minutes).range(0..=10080));
+                            ui.end_row();
+                        });
+                    }
+
+                    if ui.button("Clear cache").clicked() {
+                        ResponseCache::clear(&settings.cache);
+                    }
+
+                    ui.separator();
+                    ui.label("Network").on_hover_text("Failed requests, rate limits (429) and server errors (5xx) are retried with an increasing delay, honouring the server's Retry-After.");
+                    egui::Grid::new("http_grid").show(ui, |ui| {
+                        ui.label("Characters checked at once");
+                        ui.add(egui::DragValue::new(&mut settings.check_concurrency).range(1..=30))
+                            .on_hover_text("Each character's profile, achievements and reputation are fetched together, so every one of these makes up to three requests at a time.");
+                        ui.end_row();
+
+                        ui.label("Recheck unchanged sign-ups after (minutes)");
+                        ui.add(egui::DragValue::new(&mut settings.recheck_after_minutes).range(0..=1440))
+                            .on_hover_text("Rechecking an event only checks new and changed sign-ups. Unchanged ones keep their results until they're older than this, 0 rechecks everyone.");
+                        ui.end_row();
+
+                        ui.label("Watch interval (minutes)");
+                        ui.add(egui::DragValue::new(&mut settings.watch_interval_minutes).range(1..=120))
+                            .on_hover_text("How often a watched event is polled for sign-up changes. Takes effect the next time you start watching.");
+                        ui.end_row();
+
+                        ui.label("Timeout (seconds)");
+                        ui.add(egui::DragValue::new(&mut settings.http.timeout_secs).range(1..=300));
+                        ui.end_row();
+
+                        ui.label("Connect timeout (seconds)");
+                        ui.add(egui::DragValue::new(&mut settings.http.connect_timeout_secs).range(1..=120));
+                        ui.end_row();
+
+                        ui.label("Retries");
+                        ui.add(egui::DragValue::new(&mut settings.http.max_retries).range(0..=10));
+                        ui.end_row();
+
+                        ui.label("Retry delay (ms)");
+                        ui.add(egui::DragValue::new(&mut settings.http.backoff_base_ms).range(0..=10000))
+                            .on_hover_text("Doubles with every retry.");
+                        ui.end_row();
+
+                        ui.label("Requests per second per site");
+                        ui.add(egui::DragValue::new(&mut settings.http.requests_per_second).range(0..=100))
+                            .on_hover_text("0 disables the limit.");
+                        ui.end_row();
+
+                        ui.label("Proxy");
+                        ui.add(egui::TextEdit::singleline(&mut settings.http.proxy).hint_text("http://host:port").desired_width(250.0));
+                        ui.end_row();
+
+                        ui.label("User agent");
+                        ui.add(egui::TextEdit::singleline(&mut settings.http.user_agent).desired_width(250.0));
+                        ui.end_row();
+                    });
+                });
+
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        close
+    }
+}
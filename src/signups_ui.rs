@@ -1,11 +1,11 @@
 use std::collections::BTreeMap;
 
-use chrono::{DateTime, Local, TimeZone, Utc};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
 use egui::{CentralPanel, Hyperlink, Label, RichText, SidePanel, Ui, epaint::color};
 use tracing::info;
 use tracing_subscriber::fmt::format;
 
-use crate::{SHOULD_RECHECK_ALL, SHOULD_RECHECK_ATTENDANCE, checker::{armory_checker::RaidProgressStatus, check_player::PlayerData, gear_checker::GearChecker, raid_sheet::{Player, RAID_PLAN_CANCELLED, RAID_PLAN_UNCONFIRMED, RaidSheetType}, saved_checker::SavedChecker}, config::{self, expansion_config::ExpansionsConfig, settings::PriorityChecks}};
+use crate::{SHOULD_RECHECK_ALL, SHOULD_RECHECK_ATTENDANCE, checker::{armory_checker::RaidProgressStatus, check_player::PlayerData, gear_checker::GearChecker, readiness::ReadinessChecker, rule_checker::{PlayerIssue, RuleChecker}, raid_sheet::{Player, RAID_PLAN_CANCELLED, RAID_PLAN_UNCONFIRMED, RaidSheetType}, saved_checker::ResetSchedule}, config::{self, expansion_config::ExpansionsConfig, region::armory_character_path, rules::{RuleField, Severity}, waivers::{Waiver, WaiverTarget}}};
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 struct BossKey {
@@ -13,21 +13,209 @@ struct BossKey {
     boss_id: usize,
 }
 
+// Why a player isn't fine, every one of the preset's rules they match. The first issue that isn't waived decides their colour.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerFlag {
+    Skipped,
+    Failed(Vec<PlayerIssue>),
+}
+
+impl PlayerFlag {
+    pub fn message(&self) -> String {
+        match self {
+            PlayerFlag::Skipped => "Not checked".to_owned(),
+            PlayerFlag::Failed(issues) => issues.iter().filter(|x| !x.is_waived()).map(|x| x.category.as_str()).collect::<Vec<_>>().join(", "),
+        }
+    }
+}
+
+// From the issues worked out when the player was last refreshed.
+pub fn player_flag(player: &PlayerData) -> Option<PlayerFlag> {
+    if player.skip_reason.is_some() {
+        return Some(PlayerFlag::Skipped);
+    }
+
+    if player.issues.iter().all(|x| x.is_waived()) {
+        None
+    } else {
+        Some(PlayerFlag::Failed(player.issues.clone()))
+    }
+}
+
+// Works the gear results, issues and readiness out again if the settings changed since they last were. Returns whether they did.
+fn refresh_player(settings: &config::settings::Settings, player: &mut PlayerData, expansions: &ExpansionsConfig, event_start: Option<i64>) -> bool {
+    if settings.dirty_state == player.dirty_state {
+        return false;
+    }
+
+    if player.character.gear.len() > 0 {
+        let (bad_gear, bad_socket, bad_item, embelishments) = GearChecker::check_gear(&player.character, player.requirements(&settings.current_preset).slots, expansions);
+        let pvp_gear = GearChecker::check_pvp_gear(&player.character.gear, expansions);
+        let tier_count = GearChecker::check_tier_pieces(&player.character.gear, expansions);
+        player.bad_gear = bad_gear;
+        player.bad_socket = bad_socket;
+        player.bad_special_item = bad_item;
+        player.num_embelishments = embelishments;
+        player.pvp_gear = pvp_gear;
+        player.tier_count = tier_count;
+    }
+    player.issues = if player.skip_reason.is_none() {
+        RuleChecker::issues(settings, player, event_start)
+    } else {
+        Vec::new()
+    };
+    player.readiness = ReadinessChecker::check(settings, expansions, player);
+    player.dirty_state = settings.dirty_state;
+    true
+}
+
+fn severity_colour(severity: Severity) -> egui::Color32 {
+    match severity {
+        Severity::Blocker => egui::Color32::from_rgb(220, 50, 50),
+        Severity::Warning => egui::Color32::from_rgb(230, 180, 0),
+        Severity::Info => egui::Color32::from_rgb(100, 150, 230),
+    }
+}
+
+// Which sign-ups the list shows.
+#[derive(Clone, PartialEq)]
+enum IssueFilter {
+    Everyone,
+    AnyIssue,
+    Category(String), // Rule name
+}
+
+impl IssueFilter {
+    fn as_str(&self) -> &str {
+        match self {
+            IssueFilter::Everyone => "Everyone",
+            IssueFilter::AnyIssue => "Any issue",
+            IssueFilter::Category(category) => category,
+        }
+    }
+
+    fn shows(&self, player: &PlayerData) -> bool {
+        match self {
+            IssueFilter::Everyone => true,
+            IssueFilter::AnyIssue => player_flag(player).is_some(),
+            IssueFilter::Category(category) => player.issues.iter().any(|x| &x.category == category && !x.is_waived()),
+        }
+    }
+}
+
+// The order players are listed in within each role.
+#[derive(Clone, Copy, PartialEq)]
+enum ListOrder {
+    SignUp,
+    LeastReady,
+    MostReady,
+}
+
+impl ListOrder {
+    const ALL: [ListOrder; 3] = [ListOrder::SignUp, ListOrder::LeastReady, ListOrder::MostReady];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ListOrder::SignUp => "Sign-up order",
+            ListOrder::LeastReady => "Least ready first",
+            ListOrder::MostReady => "Most ready first",
+        }
+    }
+
+    // Players without a score (not checked) go last either way.
+    fn sort(&self, players: &mut [&mut PlayerData]) {
+        let score = |player: &PlayerData| player.readiness.as_ref().map(|x| x.score);
+        match self {
+            ListOrder::SignUp => {},
+            ListOrder::LeastReady => players.sort_by(|a, b| score(a).unwrap_or(f32::MAX).total_cmp(&score(b).unwrap_or(f32::MAX))),
+            ListOrder::MostReady => players.sort_by(|a, b| score(b).unwrap_or(-1.0).total_cmp(&score(a).unwrap_or(-1.0))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum WaiverExpiry {
+    Never,
+    Lockouts,
+    Date,
+}
+
+impl WaiverExpiry {
+    const ALL: [WaiverExpiry; 3] = [WaiverExpiry::Never, WaiverExpiry::Lockouts, WaiverExpiry::Date];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            WaiverExpiry::Never => "Until removed",
+            WaiverExpiry::Lockouts => "For lockouts",
+            WaiverExpiry::Date => "Until date",
+        }
+    }
+}
+
+// The waiver being added on the player info.
+struct WaiverForm {
+    target: Option<WaiverTarget>,
+    expiry: WaiverExpiry,
+    lockouts: u32,
+    date: String, // YYYY-MM-DD
+    note: String,
+}
+
+impl Default for WaiverForm {
+    fn default() -> Self {
+        Self {
+            target: None,
+            expiry: WaiverExpiry::Lockouts,
+            lockouts: 1,
+            date: String::new(),
+            note: String::new(),
+        }
+    }
+}
+
+impl WaiverForm {
+    // Unix seconds, a lockout count runs until the end of that many lockouts counting the event's one, or the current one without an event.
+    fn expires(&self, settings: &config::settings::Settings, event_start: Option<i64>) -> Result<Option<i64>, String> {
+        match self.expiry {
+            WaiverExpiry::Never => Ok(None),
+            WaiverExpiry::Lockouts => {
+                let mut lockout = ResetSchedule::for_region(settings.current_preset.region).lockout_for_event(event_start);
+                for _ in 1..self.lockouts {
+                    lockout = lockout.next();
+                }
+                Ok(Some(lockout.end.timestamp()))
+            },
+            WaiverExpiry::Date => {
+                let date = NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d").map_err(|_| "Enter the date as YYYY-MM-DD.".to_owned())?;
+                // Waived through the end of the day.
+                let end = date.succ_opt().and_then(|x| x.and_hms_opt(0, 0, 0)).and_then(|x| Local.from_local_datetime(&x).earliest());
+                end.map(|x| Some(x.timestamp())).ok_or_else(|| "That date can't be used.".to_owned())
+            },
+        }
+    }
+}
+
 pub struct SignUpsUI {
-    pub target_player: Option<PlayerData>
+    pub target_player: Option<PlayerData>,
+    issue_filter: IssueFilter,
+    list_order: ListOrder,
+    waiver_form: WaiverForm,
 }
 
 impl Default for SignUpsUI {
     fn default() -> Self {
         Self {
-            target_player: None
+            target_player: None,
+            issue_filter: IssueFilter::Everyone,
+            list_order: ListOrder::SignUp,
+            waiver_form: WaiverForm::default(),
         }
     }
 }
 
 impl SignUpsUI {
     pub fn draw_signups(&mut self, ctx: &eframe::egui::Context, settings: &mut config::settings::Settings, expansions: &ExpansionsConfig, primary_people: &mut Vec<PlayerData>, 
-        queued_people: &mut Vec<PlayerData>, sheet_type: RaidSheetType, should_recheck: &mut u8, clear_target: &mut bool, checked_player: &mut Option<PlayerData>) -> Option<PlayerData> {
+        queued_people: &mut Vec<PlayerData>, sheet_type: RaidSheetType, should_recheck: &mut u8, clear_target: &mut bool, checked_player: &mut Option<PlayerData>, event_start: Option<i64>) -> Option<PlayerData> {
         
         let mut recheck_player = None;
         if *clear_target {
@@ -40,7 +228,7 @@ impl SignUpsUI {
         .show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    if ui.button("Recheck").on_hover_text("Rechecks the sign-ups.").clicked() {
+                    if ui.button("Recheck").on_hover_text("Rechecks new and changed sign-ups, unchanged ones are only rechecked once their results are older than set in the settings.").clicked() {
                         *should_recheck = SHOULD_RECHECK_ALL;
                     }
                     
@@ -53,32 +241,57 @@ impl SignUpsUI {
                     }
                 });      
 
+                ui.horizontal(|ui| {
+                    ui.label("Show");
+                    egui::ComboBox::from_id_salt("issue_filter")
+                        .selected_text(self.issue_filter.as_str().to_owned())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.issue_filter, IssueFilter::Everyone, IssueFilter::Everyone.as_str());
+                            ui.selectable_value(&mut self.issue_filter, IssueFilter::AnyIssue, IssueFilter::AnyIssue.as_str());
+                            for rule in settings.current_preset.rules.iter().filter(|x| x.enabled) {
+                                let category = IssueFilter::Category(rule.name.clone());
+                                ui.selectable_value(&mut self.issue_filter, category, rule.name.as_str());
+                            }
+                        });
+                }).response.on_hover_text("Only lists the sign-ups with an issue from the chosen rule.");
+
+                ui.horizontal(|ui| {
+                    ui.label("Order");
+                    egui::ComboBox::from_id_salt("list_order")
+                        .selected_text(self.list_order.as_str())
+                        .show_ui(ui, |ui| {
+                            for order in ListOrder::ALL {
+                                ui.selectable_value(&mut self.list_order, order, order.as_str());
+                            }
+                        });
+                }).response.on_hover_text("Orders each role by readiness score, weighted as set in the preset's readiness settings.");
+
                 let roles = ["Tank", "Healer", "Melee", "Ranged", "DPS", "Skipped"];
 
+                // Refreshed up front so the filter and the order see the current settings, the rows below need settings mutably.
+                for player in primary_people.iter_mut().chain(queued_people.iter_mut()) {
+                    self.refresh_player(settings, player, expansions, event_start);
+                }
+                let hidden: Vec<String> = primary_people.iter().chain(queued_people.iter())
+                    .filter(|x| !self.issue_filter.shows(x))
+                    .map(|x| x.discord_id.clone())
+                    .collect();
+                let in_role = |player: &PlayerData, role: &str| (player.class_name == role.to_lowercase() || player.role_name == role.to_lowercase()) &&
+                    !hidden.contains(&player.discord_id);
 
                 let mut primary_players = primary_people.clone();
                 for role in roles.iter() {
                     ui.push_id(format!("{role}P"), |ui| {
-                        if primary_people.iter_mut().find(|x| x.class_name == role.to_lowercase() || x.role_name == role.to_lowercase()).is_none() {
+                        if !primary_people.iter().any(|x| in_role(x, role)) {
                             return;
                         }
                         egui::CollapsingHeader::new(*role)
                         .default_open(true)
                         .show(ui, |ui| {
-                            for player in primary_people.iter_mut().filter(|x| x.class_name == role.to_lowercase() || x.role_name == role.to_lowercase()) {
-                                let mut label_name = if sheet_type == RaidSheetType::Classes {
-                                    format!("{} ({})", player.name.clone(), player.class_name.clone())
-                                } else {
-                                    player.name.clone()
-                                };
-
-                                if settings.current_preset.regulars.as_ref().unwrap_or(&BTreeMap::new()).get(&player.discord_id).is_some() {
-                                    label_name = format!("⭐ {}", label_name);
-                                }
-
-                                if ui.label(egui::RichText::new(label_name).color(self.colour_player_label(settings, player, expansions))).clicked() {
-                                    self.target_player = Some(player.clone());
-                                }
+                            let mut players: Vec<&mut PlayerData> = primary_people.iter_mut().filter(|x| in_role(x, role)).collect();
+                            self.list_order.sort(&mut players);
+                            for player in players {
+                                self.draw_player_row(ui, settings, expansions, player, &sheet_type, event_start);
 
                                 primary_players.remove(primary_players.iter().position(|x| x.discord_id == player.discord_id).unwrap());
                             }
@@ -94,27 +307,17 @@ impl SignUpsUI {
                 let mut queued_players = queued_people.clone();
                 for role in roles.iter() {
                     ui.push_id(format!("{role}S"), |ui| {
-                        if queued_people.iter().find(|x| x.class_name == role.to_lowercase() || x.role_name == role.to_lowercase()).is_none() {
+                        if !queued_people.iter().any(|x| in_role(x, role)) {
                             return;
                         }
                         
                         egui::CollapsingHeader::new(*role)
                         .default_open(true)
                         .show(ui, |ui| {
-                            for player in queued_people.iter_mut().filter(|x| x.class_name == role.to_lowercase() || x.role_name == role.to_lowercase()) {
-                                let mut label_name = if sheet_type == RaidSheetType::Classes {
-                                    format!("{} ({})", player.name.clone(), player.class_name.clone())
-                                } else {
-                                    player.name.clone()
-                                };
-
-                                if settings.current_preset.regulars.as_ref().unwrap_or(&BTreeMap::new()).get(&player.discord_id).is_some() {
-                                    label_name = format!("⭐ {}", label_name);
-                                }
-
-                                if ui.label(egui::RichText::new(label_name).color(self.colour_player_label(settings, player, expansions))).clicked() {
-                                    self.target_player = Some(player.clone());
-                                }
+                            let mut players: Vec<&mut PlayerData> = queued_people.iter_mut().filter(|x| in_role(x, role)).collect();
+                            self.list_order.sort(&mut players);
+                            for player in players {
+                                self.draw_player_row(ui, settings, expansions, player, &sheet_type, event_start);
 
                                 queued_players.remove(queued_players.iter().position(|x| x.discord_id == player.discord_id).unwrap());
                             }
@@ -127,9 +330,9 @@ impl SignUpsUI {
         CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 if self.target_player.is_none() {
-                    self.draw_summary(ui, settings, primary_people, queued_people);
+                    self.draw_summary(ui, settings, expansions, primary_people, queued_people, event_start);
                 } else {
-                    if self.draw_player_info(ui, settings, expansions, &mut None) == true {
+                    if self.draw_player_info(ui, settings, expansions, &mut None, event_start) == true {
                         recheck_player = Some(self.target_player.clone().unwrap());
                     }
                 }
@@ -142,7 +345,7 @@ impl SignUpsUI {
                 .resizable(true)
                 .show(ctx, |ui| {
                     egui::ScrollArea::vertical().show(ui, |ui| {
-                        self.draw_player_info(ui, settings, expansions, checked_player);
+                        self.draw_player_info(ui, settings, expansions, checked_player, None);
                     });
                     if ui.button("Close").clicked() {
                         *checked_player = None;
@@ -153,130 +356,186 @@ impl SignUpsUI {
         recheck_player
     }
 
-    pub fn colour_player_label(&mut self, settings: &mut config::settings::Settings, player: &mut PlayerData, expansions: &ExpansionsConfig) -> egui::Color32 {
-        // Check ilvl
-        if player.skip_reason.is_some() {
-            let skip_colour = settings.current_preset.skip_colour.unwrap();
-            return egui::Color32::from_rgb(skip_colour[0], skip_colour[1], skip_colour[2]);
-        }
-
-        if settings.dirty_state != player.dirty_state {
-            if player.character.gear.len() > 0 {
-                let (bad_gear, bad_socket, bad_item, embelishments) = GearChecker::check_gear(&player.character, settings, expansions);
-                let pvp_gear = GearChecker::check_pvp_gear(&player.character.gear, expansions);
-                let tier_count = GearChecker::check_tier_pieces(&player.character.gear, expansions);
-                player.bad_gear = bad_gear;
-                player.bad_socket = bad_socket;
-                player.bad_special_item = bad_item;
-                player.num_embelishments = embelishments;
-                player.pvp_gear = pvp_gear;
-                player.tier_count = tier_count;
-            }
-            player.dirty_state = settings.dirty_state;
-           
-            if self.target_player.is_some() && self.target_player.as_ref().unwrap().discord_id == player.discord_id {
+    // The player's name in their colour, followed by a badge counting their issues of each severity, and their readiness when ordering by it.
+    fn draw_player_row(&mut self, ui: &mut Ui, settings: &mut config::settings::Settings, expansions: &ExpansionsConfig, player: &mut PlayerData,
+        sheet_type: &RaidSheetType, event_start: Option<i64>) {
+        let mut label_name = if *sheet_type == RaidSheetType::Classes {
+            format!("{} ({})", player.name.clone(), player.display_class())
+        } else {
+            player.name.clone()
+        };
+
+        if settings.current_preset.regulars.as_ref().unwrap_or(&BTreeMap::new()).get(&player.discord_id).is_some() {
+            label_name = format!("⭐ {}", label_name);
+        }
+
+        let colour = self.colour_player_label(settings, player, expansions, event_start);
+
+        ui.horizontal(|ui| {
+            if ui.label(egui::RichText::new(label_name).color(colour)).clicked() {
                 self.target_player = Some(player.clone());
             }
-        }
 
-        for prio in settings.current_preset.check_priority.iter() {
-            match prio {
-                PriorityChecks::SavedKills => {
-                    for raid in &player.raid_data {
-                        if settings.current_preset.saved_raids.get(&(*raid.0 as i32)).is_some() {
-                            for boss in &raid.1.bosses {
-                                for difficulty in &boss.1.difficulties {
-                                    let saved_difficulty = settings.current_preset.saved_raids.get(&(*raid.0 as i32)).unwrap().difficulty.get(&(*difficulty.0 as i32));
-                                    if saved_difficulty.is_some() {
-                                        if saved_difficulty.unwrap().boss_ids.get(boss.1.boss_id).is_some() {
-                                            if difficulty.1.boss_kill_time.is_some() {
-                                                if difficulty.1.boss_kill_time.unwrap() > SavedChecker::get_wednesday_reset_timestamp() as u64 {
-                                                    let saved_colour = settings.current_preset.saved_colour.unwrap();
-                                                    return egui::Color32::from_rgb(saved_colour[0], saved_colour[1], saved_colour[2]);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }                    
-                },
+            for severity in Severity::ALL {
+                let of_severity: Vec<&PlayerIssue> = player.issues.iter().filter(|x| x.severity == severity && !x.is_waived()).collect();
+                if of_severity.is_empty() {
+                    continue;
+                }
+                let badge = egui::RichText::new(format!(" {} ", of_severity.len())).small().color(egui::Color32::BLACK).background_color(severity_colour(severity));
+                ui.label(badge).on_hover_text(of_severity.iter().map(|x| x.message.as_str()).collect::<Vec<_>>().join("\n"));
+            }
 
-                PriorityChecks::Ilvl => {
-                    if player.ilvl < settings.current_preset.average_ilvl{
-                        let ilvl_colour = settings.current_preset.ilvl_colour.unwrap();
-                        return egui::Color32::from_rgb(ilvl_colour[0], ilvl_colour[1], ilvl_colour[2]);
-                    }
-                },
+            let waived: Vec<&PlayerIssue> = player.issues.iter().filter(|x| x.is_waived()).collect();
+            if !waived.is_empty() {
+                let badge = egui::RichText::new(format!(" {} ", waived.len())).small().color(egui::Color32::BLACK).background_color(egui::Color32::GRAY);
+                ui.label(badge).on_hover_text(waived.iter().map(|x| format!("{} (waived)", x.message)).collect::<Vec<_>>().join("\n"));
+            }
 
-                PriorityChecks::Unkilled => {
-                    for raid in &player.raid_data {
-                        if settings.current_preset.required_raids.get(&(*raid.0 as i32)).is_some() {
-                            for boss in &raid.1.bosses {
-                                for difficulty in &boss.1.difficulties {
-                                    let required_difficulties = settings.current_preset.required_raids.get(&(*raid.0 as i32)).unwrap().difficulty.get(&(*difficulty.0 as i32));
-                                    if required_difficulties.is_some() {
-                                        if required_difficulties.unwrap().boss_ids.get(boss.1.boss_id).is_some() {
-                                            if difficulty.1.killed_before == false {
-                                                let unkilled_colour = settings.current_preset.unkilled_colour.unwrap();
-                                                return egui::Color32::from_rgb(unkilled_colour[0], unkilled_colour[1], unkilled_colour[2]);
-                                            }
-                                        }
-                                    }
-                                }
+            if let (Some(readiness), false) = (&player.readiness, self.list_order == ListOrder::SignUp) {
+                ui.label(egui::RichText::new(format!("{:.0}", readiness.score)).small().weak()).on_hover_text(readiness.describe());
+            }
+        });
+    }
+
+    // The player's waivers and a form to add one for a rule or a slot they have gear on.
+    fn draw_waivers(&mut self, ui: &mut Ui, settings: &mut config::settings::Settings, player: &PlayerData, event_start: Option<i64>) {
+        let at = event_start.unwrap_or_else(|| Utc::now().timestamp());
+        let waivers = settings.current_preset.waivers.get(&player.discord_id).cloned().unwrap_or_default();
+        egui::CollapsingHeader::new(format!("Waivers ({})", waivers.len()))
+            .id_salt("waivers")
+            .show(ui, |ui| {
+                for (index, waiver) in waivers.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let text = egui::RichText::new(waiver.description());
+                        ui.label(if waiver.is_active(at) { text } else { text.color(egui::Color32::GRAY).strikethrough() })
+                            .on_hover_text(if waiver.is_active(at) { "Active for this event." } else { "Expired before this event." });
+                        if ui.button("Remove").clicked() {
+                            let player_waivers = settings.current_preset.waivers.entry(player.discord_id.clone()).or_default();
+                            player_waivers.remove(index);
+                            if player_waivers.is_empty() {
+                                settings.current_preset.waivers.remove(&player.discord_id);
                             }
+                            settings.dirty_state += 1;
+                            settings.save_mut();
                         }
-                    }
-                },
-
-                PriorityChecks::Enchantments => {
-                    if player.bad_gear.len() > 0 || (player.num_embelishments != -1 && player.num_embelishments < settings.current_preset.embelishments) {
-                        let bad_gear_colour = settings.current_preset.bad_gear_colour.unwrap();
-                        return egui::Color32::from_rgb(bad_gear_colour[0], bad_gear_colour[1], bad_gear_colour[2]);
-                    }
-                },
-
-                PriorityChecks::BadSocket => {
-                    if player.bad_socket.len() > 0 {
-                        let bad_socket_colour = settings.current_preset.bad_socket_colour.unwrap();
-                        return egui::Color32::from_rgb(bad_socket_colour[0], bad_socket_colour[1], bad_socket_colour[2]);
-                    }
+                    });
                 }
 
-                PriorityChecks::SpecialItem => {
-                    if player.bad_special_item.len() > 0 {
-                        let bad_gear_colour = settings.current_preset.bad_special_item_colour.unwrap();
-                        return egui::Color32::from_rgb(bad_gear_colour[0], bad_gear_colour[1], bad_gear_colour[2]);
-                    }
-                },
+                let mut targets: Vec<WaiverTarget> = settings.current_preset.rules.iter().map(|x| WaiverTarget::Category(x.name.clone())).collect();
+                let mut slots: Vec<String> = player.character.gear.values().map(|x| x.inventory_type.gear_type.to_lowercase()).collect();
+                slots.sort();
+                slots.dedup();
+                targets.extend(slots.into_iter().map(WaiverTarget::Slot));
 
-                PriorityChecks::RaidBuff => {
-                    if player.buff_status.iter().any(|x| x.1.1 > 0) {
-                        let buff_colour: [u8; 4] = settings.current_preset.buff_colour.unwrap();
-                        return egui::Color32::from_rgb(buff_colour[0], buff_colour[1], buff_colour[2]);
+                let form = &mut self.waiver_form;
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("waiver_target")
+                        .selected_text(form.target.as_ref().map_or("Waive...".to_owned(), |x| x.label()))
+                        .show_ui(ui, |ui| {
+                            for target in targets {
+                                let label = target.label();
+                                ui.selectable_value(&mut form.target, Some(target), label);
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("waiver_expiry")
+                        .selected_text(form.expiry.as_str())
+                        .show_ui(ui, |ui| {
+                            for expiry in WaiverExpiry::ALL {
+                                ui.selectable_value(&mut form.expiry, expiry, expiry.as_str());
+                            }
+                        });
+                    match form.expiry {
+                        WaiverExpiry::Never => {},
+                        WaiverExpiry::Lockouts => {
+                            ui.add(egui::DragValue::new(&mut form.lockouts).range(1..=52)).on_hover_text("Counting the event's lockout.");
+                        },
+                        WaiverExpiry::Date => {
+                            ui.add(egui::TextEdit::singleline(&mut form.date).hint_text("YYYY-MM-DD").desired_width(90.0));
+                        },
                     }
-                },
+                });
 
-                PriorityChecks::MissingTier => {
-                    if player.tier_count != -1 && player.tier_count < 4 {
-                        let tier_colour: [u8; 4] = settings.current_preset.missing_tier_colour.unwrap();
-                        return egui::Color32::from_rgb(tier_colour[0], tier_colour[1], tier_colour[2]);
+                let expires = form.expires(settings, event_start);
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut form.note).hint_text("Note...").desired_width(200.0));
+                    let can_add = form.target.is_some() && expires.is_ok();
+                    if ui.add_enabled(can_add, egui::Button::new("Add waiver")).clicked() {
+                        if let (Some(target), Ok(expires)) = (form.target.take(), &expires) {
+                            settings.current_preset.waivers.entry(player.discord_id.clone()).or_default().push(Waiver {
+                                target,
+                                expires: *expires,
+                                note: form.note.trim().to_owned(),
+                            });
+                            form.note.clear();
+                            settings.dirty_state += 1;
+                            settings.save_mut();
+                        }
+                    }
+                });
+                if let (Err(err), WaiverExpiry::Date) = (&expires, form.expiry) {
+                    if !form.date.is_empty() {
+                        ui.label(egui::RichText::new(err).color(egui::Color32::RED));
                     }
                 }
-            }
+            });
+    }
+
+    fn refresh_player(&mut self, settings: &config::settings::Settings, player: &mut PlayerData, expansions: &ExpansionsConfig, event_start: Option<i64>) {
+        if refresh_player(settings, player, expansions, event_start) && self.target_player.as_ref().is_some_and(|x| x.discord_id == player.discord_id) {
+            self.target_player = Some(player.clone());
         }
+    }
+
+    pub fn colour_player_label(&mut self, settings: &mut config::settings::Settings, player: &mut PlayerData, expansions: &ExpansionsConfig, event_start: Option<i64>) -> egui::Color32 {
+        self.refresh_player(settings, player, expansions, event_start);
 
-        egui::Color32::GREEN
+        let colour = match player_flag(player) {
+            None => return egui::Color32::GREEN,
+            Some(PlayerFlag::Skipped) => settings.current_preset.skip_colour.unwrap(),
+            Some(PlayerFlag::Failed(issues)) => issues.iter().find(|x| !x.is_waived()).map_or([0x0, 0xFF, 0x0, 0xFF], |x| x.colour),
+        };
+        egui::Color32::from_rgb(colour[0], colour[1], colour[2])
     }
 
-    pub fn draw_summary(&mut self, ui: &mut Ui, settings: &mut config::settings::Settings, primary_people: &Vec<PlayerData>, queued_people: &Vec<PlayerData>) {
+    pub fn draw_summary(&mut self, ui: &mut Ui, settings: &mut config::settings::Settings, expansions: &ExpansionsConfig, primary_people: &Vec<PlayerData>, queued_people: &Vec<PlayerData>, event_start: Option<i64>) {
         let combined = primary_people.iter().chain(queued_people.iter()).collect::<Vec<&PlayerData>>();
         if combined.len() == 0 {
             ui.label("A general summary of the sign-ups will be shown here.");
             return;
         }
 
+        let schedule = ResetSchedule::for_region(settings.current_preset.region);
+        let lockout = schedule.lockout_for_event(event_start);
+        let next_lockout = lockout.next();
+        ui.label(format!("Saved checks use the {} lockout from {} until {}, the next lockout ends {}.", settings.current_preset.region.as_str(),
+            lockout.start.with_timezone(&Local).format("%A %d %b %H:%M"), lockout.end.with_timezone(&Local).format("%A %d %b %H:%M"),
+            next_lockout.end.with_timezone(&Local).format("%A %d %b %H:%M")));
+
+        if lockout.start > schedule.current_lockout().start {
+            ui.label(egui::RichText::new("The event is in a future lockout, kills from this week will not matter.").color(egui::Color32::YELLOW));
+        }
+        ui.label("");
+
+        let summary = ReadinessChecker::summarise(settings, expansions, &combined);
+        if summary.checked > 0 {
+            ui.label(format!("Of the {} checked sign-ups, the average readiness is {:.0} / 100 and the average item level {:.1}.", summary.checked, summary.average_score, summary.average_ilvl));
+            egui::Grid::new("readiness_summary").show(ui, |ui| {
+                for (label, share) in [
+                    ("Fully enchanted", summary.fully_enchanted),
+                    ("Sockets done", summary.sockets_filled),
+                    ("Special items", summary.special_items),
+                    ("4-piece tier", summary.four_piece),
+                    ("Embellishments", summary.embellished),
+                    ("Required kills", summary.all_required_kills),
+                ] {
+                    ui.label(label);
+                    ui.add(egui::ProgressBar::new(share).desired_width(150.0).text(format!("{:.0}%", share * 100.0)));
+                    ui.end_row();
+                }
+            });
+            ui.label("");
+        }
+
         let mut unconfirmed = String::default();
         let mut cancelled: String = String::default();
         for player in combined.iter() {
@@ -300,7 +559,7 @@ impl SignUpsUI {
         }
     }
 
-    pub fn draw_player_info(&mut self, ui: &mut Ui, settings: &mut config::settings::Settings, expansions: &ExpansionsConfig, checked_player: &mut Option<PlayerData>) -> bool {
+    pub fn draw_player_info(&mut self, ui: &mut Ui, settings: &mut config::settings::Settings, expansions: &ExpansionsConfig, checked_player: &mut Option<PlayerData>, event_start: Option<i64>) -> bool {
 
         let mut should_recheck = false;
         let mut player = if checked_player.is_some() {
@@ -313,17 +572,12 @@ impl SignUpsUI {
             }
         };
 
-        if settings.dirty_state != player.dirty_state {
-            let (bad_gear, bad_socket, bad_item, embelishments) = GearChecker::check_gear(&player.character, settings, expansions);
-            let pvp_gear = GearChecker::check_pvp_gear(&player.character.gear, expansions);
-            let tier_count = GearChecker::check_tier_pieces(&player.character.gear, expansions);
-            player.bad_gear = bad_gear;
-            player.bad_socket = bad_socket;
-            player.bad_special_item = bad_item;
-            player.num_embelishments = embelishments;
-            player.pvp_gear = pvp_gear;
-            player.tier_count = tier_count;
-            self.target_player = Some(player.clone());
+        if refresh_player(settings, &mut player, expansions, event_start) {
+            if checked_player.is_some() {
+                *checked_player = Some(player.clone());
+            } else {
+                self.target_player = Some(player.clone());
+            }
         }
 
         if player.skip_reason.is_some() {
@@ -333,10 +587,9 @@ impl SignUpsUI {
 
         ui.horizontal(|ui| {
             ui.add(Hyperlink::from_label_and_url("Armory", format!("{}", player.armory_url)));
-            let converted_url = player.armory_url.clone().replace("worldofwarcraft.blizzard.com/en-gb", "www.warcraftlogs.com");
-            ui.add(Hyperlink::from_label_and_url("Logs", format!("{}", converted_url)));
-            let converted_url = player.armory_url.clone().replace("worldofwarcraft.blizzard.com/en-gb/character", "raider.io/characters");
-            ui.add(Hyperlink::from_label_and_url("Raider.IO", format!("{}", converted_url)));
+            let character_path = armory_character_path(&player.armory_url);
+            ui.add(Hyperlink::from_label_and_url("Logs", format!("https://www.warcraftlogs.com/{}", character_path)));
+            ui.add(Hyperlink::from_label_and_url("Raider.IO", format!("https://raider.io/{}", character_path.replacen("character/", "characters/", 1))));
             if checked_player.as_ref().is_none() && ui.button("Recheck").on_hover_text("Rechecks this player.").clicked() == true {
                 should_recheck = true;
             }
@@ -361,6 +614,34 @@ impl SignUpsUI {
             ui.add(Label::new(egui::RichText::new(format!("This player is marked as a regular ({}).", regular)).color(egui::Color32::from_rgb(255, 255, 0))));
         }
 
+        if let Some(readiness) = &player.readiness {
+            ui.label(format!("Readiness: {:.0} / 100", readiness.score)).on_hover_text(readiness.describe());
+        }
+
+        // The same issues as the sign-up list, in the same order, so the first one not waived is the colour it was listed in.
+        let issues = &player.issues;
+        if !issues.is_empty() {
+            for issue in issues.iter() {
+                ui.horizontal(|ui| {
+                    if let Some(waived) = &issue.waived {
+                        ui.label(egui::RichText::new(" Waived ").small().color(egui::Color32::BLACK).background_color(egui::Color32::GRAY));
+                        ui.label(egui::RichText::new(format!("{}: {}", issue.category, issue.message)).color(egui::Color32::GRAY).strikethrough())
+                            .on_hover_text(format!("Waived by {}", waived));
+                    } else {
+                        ui.label(egui::RichText::new(format!(" {} ", issue.severity.as_str())).small().color(egui::Color32::BLACK).background_color(severity_colour(issue.severity)));
+                        ui.label(egui::RichText::new(format!("{}: {}", issue.category, issue.message)).color(egui::Color32::from_rgb(issue.colour[0], issue.colour[1], issue.colour[2])));
+                    }
+                });
+            }
+        }
+
+        if checked_player.is_none() {
+            self.draw_waivers(ui, settings, &player, event_start);
+        }
+        if !issues.is_empty() || checked_player.is_none() {
+            ui.label("");
+        }
+
         let last_updated = player.character.last_updated_timestamp.epoch / 1000;
         let last_updated: DateTime<Utc> = Utc.timestamp_opt(last_updated as i64, 0).unwrap();
         let last_updated_local: DateTime<Local> = last_updated.with_timezone(&Local);
@@ -388,8 +669,12 @@ impl SignUpsUI {
             ui.label(egui::RichText::new(format!("{} is level {}! The current max is {}", player.name, player.lvl, max_level)).color(egui::Color32::RED));
         }
 
-        if player.ilvl < settings.current_preset.average_ilvl {
-            ui.label(format!("{} has an ilvl of {} which is below the average ilvl of {}", player.name.clone(), player.ilvl, settings.current_preset.average_ilvl));
+        // Only what an issue above still holds against the player is spelled out, disabled rules and waivers don't show up here.
+        let flags = |field: RuleField| RuleChecker::flags(settings, issues, field);
+        let requirements = player.requirements(&settings.current_preset);
+        let (required_ilvl, required_embelishments) = (requirements.average_ilvl, requirements.embelishments);
+        if player.ilvl < required_ilvl && flags(RuleField::ItemLevel) {
+            ui.label(format!("{} has an ilvl of {} which is below the average ilvl of {}", player.name.clone(), player.ilvl, required_ilvl));
 
             if player.pvp_gear {
                 ui.label(egui::RichText::new("This player has PvP gear equipped, which may be the cause of the low ilvl.").color(egui::Color32::YELLOW));
@@ -421,7 +706,7 @@ impl SignUpsUI {
         }
 
         let mut raid_name = String::new();
-        if boss_killed.len() > 0 {
+        if boss_killed.len() > 0 && flags(RuleField::MissingKills) {
             ui.label(format!("{} has not killed the following bosses:", player.name.clone()));
             for boss in boss_killed.iter() {
                 if raid_name != boss.1.0 {
@@ -437,6 +722,7 @@ impl SignUpsUI {
             ui.label("");
         }
 
+        let lockout = ResetSchedule::for_region(settings.current_preset.region).lockout_for_event(event_start);
         let mut saved_bosses: BTreeMap<BossKey, (String, String, Vec<String>, u64)> = BTreeMap::new();
         for raid in &player.raid_data {
             if settings.current_preset.saved_raids.get(&(*raid.0 as i32)).is_some() {
@@ -446,7 +732,7 @@ impl SignUpsUI {
                         if saved_difficulty.is_some() {
                             if saved_difficulty.unwrap().boss_ids.get(boss.1.boss_id).is_some() {
                                 if difficulty.1.boss_kill_time.is_some() {
-                                    if difficulty.1.boss_kill_time.unwrap() > SavedChecker::get_wednesday_reset_timestamp() as u64 {
+                                    if lockout.contains(difficulty.1.boss_kill_time.unwrap()) {
                                         let status = saved_bosses.entry(BossKey { raid_id: *raid.0, boss_id: boss.1.boss_id }).or_default();
                                         status.0 = raid.1.raid_name.clone();
                                         status.1 = boss.1.boss_name.clone();
@@ -460,9 +746,14 @@ impl SignUpsUI {
                 }
             }
         }
+        if event_start.is_some() && lockout.start > ResetSchedule::for_region(settings.current_preset.region).current_lockout().start {
+            ui.label(egui::RichText::new("The event is in a future lockout, kills from this week will not matter.").color(egui::Color32::YELLOW));
+            ui.label("");
+        }
+
         let mut raid_name = String::new();
         if saved_bosses.len() > 0 {
-            ui.label(format!("{} is saved to these bosses this reset:", player.name.clone()));
+            ui.label(format!("{} is saved to these bosses for the event's reset:", player.name.clone()));
             for boss in saved_bosses.iter() {
                 if raid_name != boss.1.0 {
                     raid_name = boss.1.0.clone();
@@ -479,32 +770,40 @@ impl SignUpsUI {
             ui.label("");
         }
 
-        let gear_issue = player.bad_gear.len() > 0 || player.bad_socket.len() > 0 || player.bad_special_item.len() > 0 || (player.num_embelishments != -1 && player.num_embelishments < settings.current_preset.embelishments);
+        let show_bad_gear = player.bad_gear.len() > 0 && flags(RuleField::BadEnchants);
+        let show_bad_special_item = player.bad_special_item.len() > 0 && flags(RuleField::MissingSpecialItems);
+        let show_bad_socket = player.bad_socket.len() > 0 && flags(RuleField::MissingSockets);
+        let show_embelishments = player.num_embelishments != -1 && player.num_embelishments < required_embelishments && flags(RuleField::Embellishments);
+        let gear_issue = show_bad_gear || show_bad_socket || show_bad_special_item || show_embelishments;
         if gear_issue {
             ui.label(format!("{} has gear that does not meet the requirements:", player.name.clone()));
         }
 
-        if player.bad_gear.len() > 0 {
+        if show_bad_gear {
             for gear in player.bad_gear.iter() {
                 ui.label(format!("\t{}", gear));
             }
         }
 
-        if player.bad_special_item.len() > 0 {
-            let special_item_colour = settings.current_preset.bad_special_item_colour.unwrap();
+        if show_bad_special_item {
+            // Shown in the colour of the rule that looks at special items, if there is one.
+            let special_item_colour = settings.current_preset.rules.iter()
+                .find(|x| x.enabled && x.conditions.iter().any(|c| c.field == RuleField::MissingSpecialItems))
+                .map(|x| x.colour)
+                .unwrap_or([0x8B, 0x0, 0x0, 0xFF]);
             for gear in player.bad_special_item.iter() {
                 ui.label(egui::RichText::new(format!("\t{}", gear)).color(egui::Color32::from_rgb(special_item_colour[0], special_item_colour[1], special_item_colour[2])));
             }
         }
 
-        if player.bad_socket.len() > 0 {
+        if show_bad_socket {
             for gear in player.bad_socket.iter() {
                 ui.label(format!("\t{}", gear));
             }
         }
 
-        if player.num_embelishments != -1 && player.num_embelishments < settings.current_preset.embelishments {
-            ui.label(egui::RichText::new(format!("{} is missing {} embelishments", player.name.clone(), settings.current_preset.embelishments - player.num_embelishments)).color(egui::Color32::from_rgb(255, 0, 0)));
+        if show_embelishments {
+            ui.label(egui::RichText::new(format!("{} is missing {} embelishments", player.name.clone(), required_embelishments - player.num_embelishments)).color(egui::Color32::from_rgb(255, 0, 0)));
         }
         
         if gear_issue {
@@ -607,4 +906,44 @@ impl SignUpsUI {
 
         should_recheck
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issues_follow_requirement_changes() {
+        let mut settings = config::settings::Settings::default();
+        settings.current_preset.average_ilvl = 630;
+        let expansions = ExpansionsConfig::default();
+        let mut player = PlayerData { ilvl: 640, lvl: 80, num_embelishments: -1, tier_count: -1, ..PlayerData::for_test("1") };
+
+        assert!(refresh_player(&settings, &mut player, &expansions, None));
+        assert!(player.issues.is_empty());
+        assert!(!refresh_player(&settings, &mut player, &expansions, None));
+
+        // What the item requirements window does when the slider moves.
+        settings.current_preset.average_ilvl = 650;
+        settings.dirty_state += 1;
+        assert!(refresh_player(&settings, &mut player, &expansions, None));
+        assert_eq!(player.issues.iter().map(|x| x.category.as_str()).collect::<Vec<_>>(), ["Bad Item Level"]);
+        assert_eq!(player_flag(&player).map(|x| x.message()), Some("Bad Item Level".to_owned()));
+    }
+
+    #[test]
+    fn lockout_waivers_count_from_the_event() {
+        let settings = config::settings::Settings::default();
+        let schedule = ResetSchedule::for_region(settings.current_preset.region);
+        let next = schedule.next_lockout();
+        let event_start = Some(next.start.timestamp() + 3600);
+        let form = WaiverForm::default();
+
+        let expires = form.expires(&settings, event_start).unwrap();
+        assert_eq!(expires, Some(next.end.timestamp()));
+        let waiver = Waiver { target: WaiverTarget::Category("Bad Item Level".to_owned()), expires, note: String::new() };
+        assert!(waiver.is_active(event_start.unwrap()));
+
+        assert_eq!(form.expires(&settings, None).unwrap(), Some(schedule.current_lockout().end.timestamp()));
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::BTreeMap, sync::mpsc::{self, Receiver}};
+
+use crate::{checker::{check_player::PlayerData, writeback::{self, WritebackAction, SIGNUP_STATUSES}}, config::{last_raid::LastRaid, settings::Settings}, http_client, signups_ui::player_flag};
+
+// Opt-in changes to the checked event on raid-helper: move flagged sign-ups to bench/tentative and note their issues on the event.
+pub struct WritebackUi {
+    event_id: String,
+    selected: BTreeMap<String, bool>, // Discord ID, whether to move them
+    class_name: String,
+    add_note: bool,
+    note: String,
+    preview: Option<Vec<WritebackAction>>,
+    receiver: Option<Receiver<(String, Result<(), String>)>>,
+    results: Vec<(String, Result<(), String>)>,
+}
+
+impl Default for WritebackUi {
+    fn default() -> Self {
+        Self {
+            event_id: String::new(),
+            selected: BTreeMap::new(),
+            class_name: SIGNUP_STATUSES[0].to_owned(),
+            add_note: false,
+            note: String::new(),
+            preview: None,
+            receiver: None,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl WritebackUi {
+    // Starts over whenever a different event was checked, with every flagged player selected and their issues as the note.
+    fn reset(&mut self, last_raid: &LastRaid, flagged: &[(&PlayerData, String)]) {
+        *self = Self::default();
+        self.event_id = last_raid.raid_id.clone();
+        self.selected = flagged.iter().map(|x| (x.0.discord_id.clone(), true)).collect();
+        self.note = flagged.iter().map(|x| format!("{}: {}", x.0.name, x.1)).collect::<Vec<_>>().join("\n");
+    }
+
+    fn actions(&self, flagged: &[(&PlayerData, String)]) -> Vec<WritebackAction> {
+        let mut actions: Vec<WritebackAction> = flagged.iter()
+            .filter(|x| self.selected.get(&x.0.discord_id).copied().unwrap_or(false))
+            .map(|x| WritebackAction::SetSignupClass {
+                discord_id: x.0.discord_id.clone(),
+                name: x.0.name.clone(),
+                class_name: self.class_name.clone(),
+            })
+            .collect();
+        if self.add_note && !self.note.trim().is_empty() {
+            actions.push(WritebackAction::AppendNote(self.note.trim().to_owned()));
+        }
+        actions
+    }
+
+    fn apply(&mut self, settings: &Settings, actions: Vec<WritebackAction>) {
+        let (sender, receiver) = mpsc::channel();
+        let raid_helper = settings.raid_helper.clone();
+        let event_id = self.event_id.clone();
+        http_client::runtime().spawn(async move {
+            for action in actions {
+                let result = writeback::apply(&raid_helper, &event_id, &action).await;
+                if sender.send((action.describe(), result)).is_err() {
+                    return;
+                }
+            }
+        });
+        self.receiver = Some(receiver);
+        self.results.clear();
+    }
+
+    // Returns true when the window was closed.
+    pub fn render(&mut self, ctx: &egui::Context, settings: &mut Settings, last_raid: &LastRaid, players: &[PlayerData]) -> bool {
+        let flagged: Vec<(&PlayerData, String)> = players.iter()
+            .filter(|x| x.needs_attention.is_none())
+            .filter_map(|x| player_flag(x).map(|flag| (x, flag.message())))
+            .collect();
+        if self.event_id != last_raid.raid_id {
+            self.reset(last_raid, &flagged);
+        }
+
+        if let Some(receiver) = &self.receiver {
+            self.results.extend(receiver.try_iter());
+        }
+
+        let mut close = false;
+        egui::Window::new("Update raid-helper")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if let Some(preview) = self.preview.clone() {
+                    self.draw_preview(ui, settings, preview);
+                    return;
+                }
+
+                ui.label(format!("Changes to {} on raid-helper. Nothing is sent until you confirm the preview.", last_raid.raid_name));
+                egui::Grid::new("writeback_key_grid").show(ui, |ui| {
+                    ui.label("API key");
+                    ui.add(egui::TextEdit::singleline(&mut settings.raid_helper.api_key).password(true))
+                        .on_hover_text("The server's raid-helper API key, use /apikey in Discord to get one.");
+                    ui.end_row();
+                });
+                ui.separator();
+
+                if flagged.is_empty() {
+                    ui.label("No flagged players.");
+                } else {
+                    ui.horizontal(|ui| {
+                        ui.label("Move selected players to");
+                        egui::ComboBox::from_id_salt("writeback_class")
+                            .selected_text(self.class_name.clone())
+                            .show_ui(ui, |ui| {
+                                for status in SIGNUP_STATUSES.iter() {
+                                    ui.selectable_value(&mut self.class_name, status.to_string(), *status);
+                                }
+                            });
+                    });
+                    egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                        for (player, issue) in flagged.iter() {
+                            let selected = self.selected.entry(player.discord_id.clone()).or_insert(false);
+                            ui.checkbox(selected, format!("{} - {}", player.name, issue));
+                        }
+                    });
+                }
+
+                ui.separator();
+                ui.checkbox(&mut self.add_note, "Add a note to the event description");
+                if self.add_note {
+                    ui.add(egui::TextEdit::multiline(&mut self.note).desired_rows(4));
+                }
+
+                ui.horizontal(|ui| {
+                    let actions = self.actions(&flagged);
+                    if ui.add_enabled(!actions.is_empty(), egui::Button::new("Preview")).clicked() {
+                        self.preview = Some(actions);
+                        self.results.clear();
+                    }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        close
+    }
+
+    fn draw_preview(&mut self, ui: &mut egui::Ui, settings: &Settings, preview: Vec<WritebackAction>) {
+        let running = self.receiver.is_some() && self.results.len() < preview.len();
+        if self.results.is_empty() && !running {
+            ui.label("The following will be changed on raid-helper:");
+        }
+
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            for (index, action) in preview.iter().enumerate() {
+                match self.results.get(index) {
+                    Some((_, Ok(()))) => ui.label(egui::RichText::new(format!("Done: {}", action.describe())).color(egui::Color32::GREEN)),
+                    Some((_, Err(err))) => ui.label(egui::RichText::new(format!("Failed: {}\n{}", action.describe(), err)).color(egui::Color32::RED)),
+                    None => ui.label(action.describe()),
+                };
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if running {
+                ui.spinner();
+                ui.label("Updating raid-helper");
+            } else if self.results.is_empty() {
+                if ui.button("Confirm").clicked() {
+                    settings.save();
+                    self.apply(settings, preview);
+                }
+                if ui.button("Back").clicked() {
+                    self.preview = None;
+                }
+            } else if ui.button("Done").clicked() {
+                self.preview = None;
+                self.receiver = None;
+                self.results.clear();
+            }
+        });
+    }
+}